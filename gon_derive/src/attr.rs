@@ -0,0 +1,235 @@
+//! Parsing of the `#[gon(...)]` attributes of containers, fields and variants.
+
+use syn::{meta::ParseNestedMeta, spanned::Spanned, Attribute, ExprPath, LitStr, Token};
+
+/// Attributes of a struct or enum.
+#[derive(Default)]
+pub struct ContainerAttrs {
+    pub rename_all: Option<RenameRule>,
+    /// Where missing fields are taken from
+    pub default: Option<DefaultValue>,
+    pub tagging: Tagging,
+    /// Fail on keys that no field reads instead of ignoring them
+    pub deny_unknown_fields: bool
+}
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        let (mut tag, mut content, mut untagged) = (None, None, None);
+        parse_items(attrs, |name, meta| {
+            match name {
+                "rename_all" => parsed.rename_all = Some(RenameRule::parse(meta)?),
+                "default" => parsed.default = Some(DefaultValue::parse(meta)?),
+                "tag" => tag = Some(string_value(meta)?),
+                "content" => content = Some((string_value(meta)?, meta.path.span())),
+                "untagged" => untagged = Some(meta.path.span()),
+                "deny_unknown_fields" => parsed.deny_unknown_fields = true,
+                _ => return Ok(false)
+            }
+            Ok(true)
+        })?;
+        parsed.tagging = match (tag, content, untagged) {
+            (None, None, None) => Tagging::External,
+            (Some(tag), None, None) => Tagging::Internal { tag },
+            (Some(tag), Some((content, _)), None) => Tagging::Adjacent { tag, content },
+            (None, None, Some(_)) => Tagging::Untagged,
+            (None, Some((_, span)), _) => return Err(syn::Error::new(span, "content requires a tag = \"...\"")),
+            (Some(_), _, Some(span)) => return Err(syn::Error::new(span, "untagged enums can't have a tag"))
+        };
+        Ok(parsed)
+    }
+}
+
+/// How the variants of an enum with data are represented.
+#[derive(Default)]
+pub enum Tagging {
+    /// `{ Variant { ... } }`, or just `Variant` for unit variants
+    #[default]
+    External,
+    /// `{ tag Variant ... }` with the fields of the variant
+    Internal { tag: String },
+    /// `{ tag Variant content { ... } }`
+    Adjacent { tag: String, content: String },
+    /// Just the content of the variant, trying each variant in order
+    Untagged
+}
+
+/// Attributes of an enum variant.
+#[derive(Default)]
+pub struct VariantAttrs {
+    pub rename: Option<String>,
+    /// Additional names accepted when converting from GON
+    pub aliases: Vec<String>,
+    /// The rule for the fields of a struct variant
+    pub rename_all: Option<RenameRule>
+}
+impl VariantAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        parse_items(attrs, |name, meta| {
+            match name {
+                "rename" => parsed.rename = Some(string_value(meta)?),
+                "alias" => parsed.aliases.push(string_value(meta)?),
+                "rename_all" => parsed.rename_all = Some(RenameRule::parse(meta)?),
+                _ => return Ok(false)
+            }
+            Ok(true)
+        })?;
+        Ok(parsed)
+    }
+}
+
+/// Attributes of a struct field.
+#[derive(Default)]
+pub struct FieldAttrs {
+    pub rename: Option<String>,
+    /// Additional keys accepted when converting from GON
+    pub aliases: Vec<String>,
+    /// The value of a missing field
+    pub default: Option<DefaultValue>,
+    /// Read the field from the object of its struct, or collect the keys no other field reads into it if it's a map
+    pub flatten: bool
+}
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        let mut flatten = None;
+        parse_items(attrs, |name, meta| {
+            match name {
+                "rename" => parsed.rename = Some(string_value(meta)?),
+                "alias" => parsed.aliases.push(string_value(meta)?),
+                "default" => parsed.default = Some(DefaultValue::parse(meta)?),
+                "flatten" => flatten = Some(meta.path.span()),
+                _ => return Ok(false)
+            }
+            Ok(true)
+        })?;
+        if let Some(span) = flatten {
+            if parsed.rename.is_some() || !parsed.aliases.is_empty() || parsed.default.is_some() {
+                return Err(syn::Error::new(span, "flatten can't be combined with rename, alias or default"));
+            }
+            parsed.flatten = true;
+        }
+        Ok(parsed)
+    }
+}
+
+/// Calls the handler with the name of every item of the `#[gon(...)]` attributes. The handler returns if it knows
+/// the item.
+fn parse_items(
+    attrs: &[Attribute],
+    mut handle: impl FnMut(&str, &ParseNestedMeta) -> syn::Result<bool>
+) -> syn::Result<()> {
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("gon")) {
+        attr.parse_nested_meta(|meta| {
+            let name = meta.path.get_ident().map(ToString::to_string).unwrap_or_default();
+            if !handle(&name, &meta)? {
+                return Err(meta.error(format!("unknown gon attribute `{name}`")));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+fn string_value(meta: &ParseNestedMeta) -> syn::Result<String> {
+    meta.value()
+        .and_then(|value| value.parse::<LitStr>())
+        .map(|s| s.value())
+        .map_err(|err| syn::Error::new(err.span(), "expected a string like name = \"...\""))
+}
+
+/// A default value from `#[gon(default)]` or `#[gon(default = "path::to::function")]`.
+pub enum DefaultValue {
+    Trait,
+    Function(ExprPath)
+}
+impl DefaultValue {
+    fn parse(meta: &ParseNestedMeta) -> syn::Result<Self> {
+        if !meta.input.peek(Token![=]) {
+            return Ok(Self::Trait);
+        }
+        meta.value()
+            .and_then(|value| value.parse::<LitStr>())
+            .and_then(|s| s.parse())
+            .map(Self::Function)
+            .map_err(|err| syn::Error::new(err.span(), "expected default or default = \"path::to::function\""))
+    }
+}
+
+/// A case convention for `rename_all`, named like the case itself.
+#[derive(Clone, Copy)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab
+}
+impl RenameRule {
+    fn parse(meta: &ParseNestedMeta) -> syn::Result<Self> {
+        Ok(match string_value(meta)?.as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            other => return Err(syn::Error::new(meta.path.span(), format!(
+                "unknown case `{other}`, expected one of lowercase, UPPERCASE, PascalCase, camelCase, snake_case, \
+                SCREAMING_SNAKE_CASE, kebab-case or SCREAMING-KEBAB-CASE"
+            )))
+        })
+    }
+
+    /// Renames a field, which is written in snake_case.
+    pub fn apply_to_field(self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_owned(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => field.split('_').map(capitalize).collect(),
+            Self::Camel => uncapitalize(&Self::Pascal.apply_to_field(field)),
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.replace('_', "-").to_ascii_uppercase()
+        }
+    }
+
+    /// Renames an enum variant, which is written in PascalCase.
+    pub fn apply_to_variant(self, variant: &str) -> String {
+        let snake = || {
+            let mut snake = String::new();
+            for (i, c) in variant.char_indices() {
+                if i > 0 && c.is_uppercase() {
+                    snake.push('_');
+                }
+                snake.push(c.to_ascii_lowercase());
+            }
+            snake
+        };
+        match self {
+            Self::Lower => variant.to_ascii_lowercase(),
+            Self::Upper => variant.to_ascii_uppercase(),
+            Self::Pascal => variant.to_owned(),
+            Self::Camel => uncapitalize(variant),
+            Self::Snake => snake(),
+            Self::ScreamingSnake => snake().to_ascii_uppercase(),
+            Self::Kebab => snake().replace('_', "-"),
+            Self::ScreamingKebab => snake().replace('_', "-").to_ascii_uppercase()
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map(|c| c.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+}
+
+fn uncapitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map(|c| c.to_ascii_lowercase().to_string() + chars.as_str()).unwrap_or_default()
+}
@@ -1,113 +1,689 @@
 #![allow(unused_variables)] // quote doesn't seem to 'use' variables properly
 
 use proc_macro::TokenStream;
-use quote::{quote, quote_spanned};
-use syn::{parse_macro_input, DeriveInput, Generics, GenericParam, parse_quote, Data, Fields, spanned::Spanned};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{parse_macro_input, DeriveInput, Generics, GenericParam, parse_quote, Data, DataEnum, Fields, FieldsNamed, spanned::Spanned, TypeParamBound, Lifetime, LifetimeParam, Ident, ext::IdentExt, Type, TypePath, Variant};
 
+use attr::{ContainerAttrs, DefaultValue, FieldAttrs, RenameRule, Tagging, VariantAttrs};
 
+mod attr;
 
 
-#[proc_macro_derive(FromGon)]
+
+
+#[proc_macro_derive(FromGon, attributes(gon))]
 pub fn derive_from_gon(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
-    let name = input.ident;
+    let name = &input.ident;
 
-    let generics = add_trait_bounds(input.generics);
+    let generics = add_trait_bounds(input.generics.clone(), parse_quote!(gon_rs::from::FromGon));
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let from_body = from_gon(&input.data);
+    let target = FromTarget {
+        gon: quote!(gon_rs::Gon),
+        from: quote!(gon_rs::from::FromGon::from_gon_with_context),
+        trait_path: quote!(gon_rs::from::FromGon)
+    };
+    let (from_body, known_keys) = match from_gon(&input, &target).and_then(|body| Ok((body, known_keys(&input, &target)?))) {
+        Ok(res) => res,
+        Err(err) => return err.to_compile_error().into()
+    };
 
     let expanded = quote! {
         impl #impl_generics gon_rs::from::FromGon for #name #ty_generics #where_clause {
             fn from_gon(gon: &gon_rs::Gon) -> std::result::Result<Self, gon_rs::from::FromGonError> {
+                <Self as gon_rs::from::FromGon>::from_gon_with_context(gon, gon_rs::from::Context::new())
+            }
+            #[allow(unused_mut, unused_variables)]
+            fn from_gon_with_context(
+                gon: &gon_rs::Gon,
+                context: gon_rs::from::Context<'_>
+            ) -> std::result::Result<Self, gon_rs::from::FromGonError> {
+                #from_body
+            }
+            #known_keys
+        }
+    };
+    proc_macro::TokenStream::from(expanded)
+}
+
+#[proc_macro_derive(FromBorrowedGon, attributes(gon))]
+pub fn derive_from_borrowed_gon(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = &input.ident;
+
+    // The impl gets an additional lifetime for the tree that outlives all lifetimes of the type.
+    let lifetime: Lifetime = parse_quote!('__gon);
+    let mut generics = add_trait_bounds(input.generics.clone(), parse_quote!(gon_rs::borrowed::FromBorrowedGon<#lifetime>));
+    let mut tree_lifetime = LifetimeParam::new(lifetime.clone());
+    tree_lifetime.bounds.extend(input.generics.lifetimes().map(|def| def.lifetime.clone()));
+    generics.params.insert(0, GenericParam::Lifetime(tree_lifetime));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let target = FromTarget {
+        gon: quote!(gon_rs::borrowed::BorrowedGon),
+        from: quote!(gon_rs::borrowed::FromBorrowedGon::from_borrowed_gon_with_context),
+        trait_path: quote!(gon_rs::borrowed::FromBorrowedGon<#lifetime>)
+    };
+    let (from_body, known_keys) = match from_gon(&input, &target).and_then(|body| Ok((body, known_keys(&input, &target)?))) {
+        Ok(res) => res,
+        Err(err) => return err.to_compile_error().into()
+    };
+
+    let expanded = quote! {
+        impl #impl_generics gon_rs::borrowed::FromBorrowedGon<#lifetime> for #name #ty_generics #where_clause {
+            fn from_borrowed_gon(gon: &#lifetime gon_rs::borrowed::BorrowedGon<#lifetime>) -> std::result::Result<Self, gon_rs::from::FromGonError> {
+                <Self as gon_rs::borrowed::FromBorrowedGon<#lifetime>>::from_borrowed_gon_with_context(gon, gon_rs::from::Context::new())
+            }
+            #[allow(unused_mut, unused_variables)]
+            fn from_borrowed_gon_with_context(
+                gon: &#lifetime gon_rs::borrowed::BorrowedGon<#lifetime>,
+                context: gon_rs::from::Context<'_>
+            ) -> std::result::Result<Self, gon_rs::from::FromGonError> {
                 #from_body
             }
+            #known_keys
         }
     };
     proc_macro::TokenStream::from(expanded)
 }
 
-fn add_trait_bounds(mut generics: Generics) -> Generics {
+#[proc_macro_derive(ToGon, attributes(gon))]
+pub fn derive_to_gon(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = &input.ident;
+
+    let generics = add_trait_bounds(input.generics.clone(), parse_quote!(gon_rs::to::ToGon));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let to_body = match to_gon(&input) {
+        Ok(body) => body,
+        Err(err) => return err.to_compile_error().into()
+    };
+
+    let expanded = quote! {
+        impl #impl_generics gon_rs::to::ToGon for #name #ty_generics #where_clause {
+            fn to_gon(&self) -> gon_rs::Gon {
+                #to_body
+            }
+        }
+    };
+    proc_macro::TokenStream::from(expanded)
+}
+
+fn add_trait_bounds(mut generics: Generics, bound: TypeParamBound) -> Generics {
     for param in &mut generics.params {
         if let GenericParam::Type(ref mut type_param) = *param {
-            type_param.bounds.push(parse_quote!(gon_rs::from::FromGon));
+            type_param.bounds.push(bound.clone());
         }
     }
     generics
 }
 
-fn from_gon(data: &Data) -> proc_macro2::TokenStream {
-    match data {
+/// The tree type, conversion function taking a `Context` and trait used by the code generated for `FromGon` and
+/// `FromBorrowedGon`.
+struct FromTarget {
+    gon: proc_macro2::TokenStream,
+    from: proc_macro2::TokenStream,
+    trait_path: proc_macro2::TokenStream
+}
+
+/// The key of a field in GON objects, following its `rename` attribute or the `rename_all` rule of its struct or
+/// variant.
+fn field_name(ident: &Ident, attrs: &FieldAttrs, rename_all: Option<RenameRule>) -> String {
+    let name = ident.unraw().to_string();
+    attrs.rename.clone().unwrap_or_else(|| rename_all.map_or_else(|| name.clone(), |rule| rule.apply_to_field(&name)))
+}
+
+/// The name of a variant in GON, following its `rename` attribute or the `rename_all` rule of the enum.
+fn variant_name(ident: &Ident, attrs: &VariantAttrs, rename_all: Option<RenameRule>) -> String {
+    let name = ident.unraw().to_string();
+    attrs.rename.clone().unwrap_or_else(|| rename_all.map_or_else(|| name.clone(), |rule| rule.apply_to_variant(&name)))
+}
+
+/// Checks if a type is written as `Option<...>`, which makes a field optional.
+fn is_option(ty: &Type) -> bool {
+    matches!(ty, Type::Path(TypePath { qself: None, path }) if path.segments.last().is_some_and(|segment| segment.ident == "Option"))
+}
+
+/// Checks if a type is written as one of the std or indexmap maps, which collect the remaining keys when flattened.
+fn is_map(ty: &Type) -> bool {
+    matches!(ty, Type::Path(TypePath { qself: None, path }) if path.segments.last().is_some_and(|segment| {
+        segment.ident == "HashMap" || segment.ident == "BTreeMap" || segment.ident == "IndexMap"
+    }))
+}
+
+/// Creates a default value.
+fn default_value(default: &DefaultValue) -> proc_macro2::TokenStream {
+    match default {
+        DefaultValue::Trait => quote!(std::default::Default::default()),
+        DefaultValue::Function(path) => quote!(#path())
+    }
+}
+
+/// A variant with exactly one unnamed field, which is represented by the value of the field.
+fn is_newtype(variant: &Variant) -> bool {
+    matches!(&variant.fields, Fields::Unnamed(fields) if fields.unnamed.len() == 1)
+}
+
+/// Container attributes that affect how the named fields of a struct or struct variant are read.
+#[derive(Default)]
+struct FieldsAttrs<'a> {
+    rename_all: Option<RenameRule>,
+    default: Option<&'a DefaultValue>,
+    deny_unknown_fields: bool,
+    /// The key of an internal tag, which is read by the enum but still known
+    tag: Option<&'a str>
+}
+
+/// The keys read by the named fields of a struct or variant.
+struct KnownKeys<'a> {
+    /// The keys and aliases of the fields that aren't flattened
+    keys: Vec<String>,
+    /// The types of flattened fields, which read further keys
+    flattened: Vec<&'a Type>,
+    /// If a flattened map collects all other keys
+    catch_all: bool
+}
+impl<'a> KnownKeys<'a> {
+    fn of(fields: &'a FieldsNamed, rename_all: Option<RenameRule>) -> syn::Result<Self> {
+        let mut known = Self { keys: Vec::new(), flattened: Vec::new(), catch_all: false };
+        for f in &fields.named {
+            let attrs = FieldAttrs::parse(&f.attrs)?;
+            if attrs.flatten && is_map(&f.ty) {
+                if known.catch_all {
+                    return Err(syn::Error::new(f.span(), "only one flattened map can collect the remaining keys"));
+                }
+                known.catch_all = true;
+            } else if attrs.flatten {
+                known.flattened.push(&f.ty);
+            } else {
+                known.keys.push(field_name(f.ident.as_ref().unwrap(), &attrs, rename_all));
+                known.keys.extend(attrs.aliases);
+            }
+        }
+        Ok(known)
+    }
+
+    /// Adds the keys of the flattened fields to `keys`, evaluating to whether all keys that are read are known.
+    fn add_flattened(&self, keys: proc_macro2::TokenStream, target: &FromTarget) -> proc_macro2::TokenStream {
+        let (types, catch_all, trait_path) = (&self.flattened, self.catch_all, &target.trait_path);
+        quote!(!#catch_all #( & <#types as #trait_path>::known_keys(#keys) )*)
+    }
+}
+
+/// The `known_keys` method of structs with named fields, so they can be flattened into other structs.
+fn known_keys(input: &DeriveInput, target: &FromTarget) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data_struct) = &input.data else { return Ok(quote!()) };
+    let Fields::Named(fields) = &data_struct.fields else { return Ok(quote!()) };
+    let known = KnownKeys::of(fields, ContainerAttrs::parse(&input.attrs)?.rename_all)?;
+    let keys = &known.keys;
+    let complete = known.add_flattened(quote!(keys), target);
+    Ok(quote! {
+        fn known_keys(keys: &mut std::vec::Vec<&'static str>) -> bool {
+            keys.extend_from_slice(&[#( #keys ),*]);
+            #complete
+        }
+    })
+}
+
+fn from_gon(input: &DeriveInput, target: &FromTarget) -> syn::Result<proc_macro2::TokenStream> {
+    let body = from_gon_body(input, target)?;
+    Ok(quote! {
+        let mut context = context.enter()?;
+        #body
+    })
+}
+
+fn from_gon_body(input: &DeriveInput, target: &FromTarget) -> syn::Result<proc_macro2::TokenStream> {
+    let container = ContainerAttrs::parse(&input.attrs)?;
+    if container.default.is_some() && !matches!(&input.data, Data::Struct(data) if matches!(data.fields, Fields::Named(_))) {
+        return Err(syn::Error::new(input.ident.span(), "#[gon(default)] is only supported on structs with named fields"));
+    }
+    match &input.data {
         Data::Struct(data_struct) => {
-            match &data_struct.fields {
-                Fields::Named(fields) => {
-                    let recurse = fields.named.iter().map(|f| {
-                        let name = &f.ident;
-                        let name_str = name.as_ref().unwrap().to_string();
-                        quote_spanned! {f.span()=>
-                            #name: gon_rs::from::FromGon::from_gon(map.get(#name_str).ok_or(gon_rs::from::FromGonError::Missing(&&#name_str))?)?,
-                        }
-                    });
-                    quote! {
-                        match gon {
-                            gon_rs::Gon::Array(_) | gon_rs::Gon::Value(_) => std::result::Result::Err(gon_rs::from::FromGonError::ExpectedObject),
-                            gon_rs::Gon::Object(map) => std::result::Result::Ok(Self {
-                                #( #recurse )*
+            let attrs = FieldsAttrs {
+                rename_all: container.rename_all,
+                default: container.default.as_ref(),
+                deny_unknown_fields: container.deny_unknown_fields,
+                tag: None
+            };
+            from_fields(&data_struct.fields, quote!(Self), &attrs, target)
+        }
+        Data::Enum(data_enum) => from_enum(data_enum, &container, target),
+        Data::Union(_) => panic!("No union support for #[derive(FromGon)] or #[derive(FromBorrowedGon)]"),
+    }
+}
+
+/// Converts `gon` into a struct or variant with the provided fields, using `constructor` to create it.
+fn from_fields(
+    fields: &Fields,
+    constructor: proc_macro2::TokenStream,
+    container: &FieldsAttrs,
+    target: &FromTarget
+) -> syn::Result<proc_macro2::TokenStream> {
+    let FromTarget { gon: gon_ty, from, trait_path } = target;
+    Ok(match fields {
+        Fields::Named(fields) => {
+            let recurse = fields.named.iter().map(|f| {
+                let name = &f.ident;
+                let attrs = FieldAttrs::parse(&f.attrs)?;
+                if attrs.flatten && is_map(&f.ty) {
+                    // not spanned to the field, since the key conversion is only useless for some map types
+                    return Ok(quote! {
+                        #name: map.iter()
+                            .filter(|(key, _)| {
+                                let key: &str = key;
+                                !known.contains(&key)
+                            })
+                            .map(|(key, val)| {
+                                let key: &str = key;
+                                std::result::Result::<_, gon_rs::from::FromGonError>::Ok((std::convert::From::from(key), #from(val, context.field())?))
                             })
+                            .collect::<std::result::Result<_, gon_rs::from::FromGonError>>()?,
+                    });
+                }
+                if attrs.flatten && is_option(&f.ty) {
+                    // an optional flattened struct is None if none of its keys are present
+                    let ty = &f.ty;
+                    return Ok(quote_spanned! {f.span()=>
+                        #name: {
+                            let mut keys = std::vec::Vec::new();
+                            if <#ty as #trait_path>::known_keys(&mut keys) && !keys.iter().any(|key| map.contains_key(*key)) {
+                                std::option::Option::None
+                            } else {
+                                #from(gon, context.flatten())?
+                            }
+                        },
+                    });
+                }
+                if attrs.flatten {
+                    return Ok(quote_spanned! {f.span()=>
+                        #name: #from(gon, context.flatten())?,
+                    });
+                }
+                let key = field_name(name.as_ref().unwrap(), &attrs, container.rename_all);
+                let aliases = &attrs.aliases;
+                // the field's own default comes first, then the container's and Options are None
+                let missing = match (&attrs.default, container.default) {
+                    (Some(default), _) => default_value(default),
+                    (None, Some(_)) => quote!(container_default.#name),
+                    (None, None) if is_option(&f.ty) => quote!(std::option::Option::None),
+                    (None, None) => quote!(return std::result::Result::Err(gon_rs::from::FromGonError::Missing(&&#key)))
+                };
+                Ok(quote_spanned! {f.span()=>
+                    #name: match map.get(#key)#( .or_else(|| map.get(#aliases)) )* {
+                        std::option::Option::Some(val) => #from(val, context.field())?,
+                        std::option::Option::None => #missing
+                    },
+                })
+            }).collect::<syn::Result<Vec<_>>>()?;
+            let container_default = container.default.map(|default| {
+                let value = default_value(default);
+                quote!(let container_default: Self = #value;)
+            });
+            // the keys of flattened fields are only known at runtime
+            let known = KnownKeys::of(fields, container.rename_all)?;
+            let keys = container.tag.iter().map(ToString::to_string).chain(known.keys.iter().cloned());
+            let known_keys = if known.flattened.is_empty() {
+                quote!(let known: &[&str] = &[#( #keys ),*];)
+            } else {
+                quote!(let mut known: std::vec::Vec<&'static str> = std::vec![#( #keys ),*];)
+            };
+            let complete = known.add_flattened(quote!(&mut known), target);
+            let deny = container.deny_unknown_fields;
+            quote! {
+                match gon {
+                    #gon_ty::Array(_) | #gon_ty::Value(_) => std::result::Result::Err(gon_rs::from::FromGonError::ExpectedObject),
+                    #gon_ty::Object(map) => {
+                        #known_keys
+                        let complete = #complete;
+                        // a flattened struct's keys are checked by the struct containing it
+                        if complete && !context.is_flattened() {
+                            for key in map.keys() {
+                                let key: &str = key;
+                                if !known.contains(&key) {
+                                    context.unknown_field(key, &known, #deny)?;
+                                }
+                            }
                         }
+                        #container_default
+                        std::result::Result::Ok(#constructor {
+                            #( #recurse )*
+                        })
                     }
                 }
-                Fields::Unnamed(fields) => {
-                    let count = fields.unnamed.len();
-                    let recurse = fields.unnamed.iter().enumerate().map(|(i, f)| {
-                        //let index = Index::from(i);
-                        quote_spanned! {f.span()=>
-                            gon_rs::from::FromGon::from_gon(&arr[#i])
+            }
+        }
+        Fields::Unnamed(fields) => {
+            let count = fields.unnamed.len();
+            let recurse = fields.unnamed.iter().enumerate().map(|(i, f)| {
+                if FieldAttrs::parse(&f.attrs)?.flatten {
+                    return Err(syn::Error::new(f.span(), "only named fields can be flattened"));
+                }
+                Ok(quote_spanned! {f.span()=>
+                    #from(&arr[#i], context.field())
+                })
+            }).collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                match gon {
+                    #gon_ty::Object(_) | #gon_ty::Value(_) => std::result::Result::Err(gon_rs::from::FromGonError::ExpectedArray),
+                    #gon_ty::Array(arr) => {
+                        if arr.len() != #count {
+                            return std::result::Result::Err(gon_rs::from::FromGonError::InvalidLength { expected: #count, found: arr.len() });
                         }
-                    });
-                    quote! {
-                        match gon {
-                            gon_rs::Gon::Object(_) | gon_rs::Gon::Value(_) => std::result::Result::Err(gon_rs::from::FromGonError::ExpectedArray),
-                            gon_rs::Gon::Array(arr) => {
-                                if arr.len() != #count {
-                                    return std::result::Result::Err(gon_rs::from::FromGonError::InvalidLength { expected: #count, found: arr.len() });
-                                }
-                                Self(#( #recurse )*)
+                        std::result::Result::Ok(#constructor(#( #recurse? ),*))
+                    }
+                }
+            }
+        }
+        Fields::Unit => {
+            quote! {
+                match gon {
+                    #gon_ty::Array(_) | #gon_ty::Value(_) => std::result::Result::Err(gon_rs::from::FromGonError::ExpectedObject),
+                    #gon_ty::Object(_) => std::result::Result::Ok(#constructor)
+                }
+            }
+        }
+    })
+}
+
+fn from_enum(data_enum: &DataEnum, container: &ContainerAttrs, target: &FromTarget) -> syn::Result<proc_macro2::TokenStream> {
+    let FromTarget { gon: gon_ty, from, .. } = target;
+    let variants = data_enum.variants.iter()
+        .map(|v| Ok((v, VariantAttrs::parse(&v.attrs)?)))
+        .collect::<syn::Result<Vec<_>>>()?;
+    // a pattern matching the name and aliases of a variant
+    let names = |v: &Variant, attrs: &VariantAttrs| {
+        let name = variant_name(&v.ident, attrs, container.rename_all);
+        let aliases = &attrs.aliases;
+        quote!(#name #( | #aliases )*)
+    };
+    // converts `gon` into the data of a variant
+    let content = |v: &Variant, attrs: &VariantAttrs| {
+        let ident = &v.ident;
+        let tag = match &container.tagging {
+            Tagging::Internal { tag } => Some(tag.as_str()),
+            _ => None
+        };
+        if is_newtype(v) {
+            let convert = match tag {
+                Some(tag) => quote!(#from(gon, context.with_tag(#tag))),
+                None => quote!(#from(gon, context.field()))
+            };
+            return Ok(quote!(std::result::Result::Ok(Self::#ident(#convert?))));
+        }
+        let fields_attrs = FieldsAttrs {
+            rename_all: attrs.rename_all,
+            deny_unknown_fields: container.deny_unknown_fields,
+            tag,
+            ..FieldsAttrs::default()
+        };
+        from_fields(&v.fields, quote!(Self::#ident), &fields_attrs, target)
+    };
+    let unknown = quote! {
+        _ => std::result::Result::Err(gon_rs::from::FromGonError::UnexpectedVariant(tag.to_owned()))
+    };
+    let tag_value = |tag: &str| quote! {
+        let tag: &str = match map.get(#tag) {
+            std::option::Option::Some(#gon_ty::Value(tag)) => tag.as_str(),
+            std::option::Option::Some(_) => return std::result::Result::Err(gon_rs::from::FromGonError::ExpectedValue),
+            std::option::Option::None => return std::result::Result::Err(gon_rs::from::FromGonError::Missing(&&#tag))
+        };
+    };
+
+    Ok(match &container.tagging {
+        Tagging::External => {
+            let (unit, data): (Vec<_>, Vec<_>) = variants.iter().partition(|(v, _)| matches!(v.fields, Fields::Unit));
+            let unit_arms = unit.iter().map(|(v, attrs)| {
+                let names = names(v, attrs);
+                let ident = &v.ident;
+                quote! { #names => std::result::Result::Ok(Self::#ident), }
+            });
+            let value = quote! {
+                #gon_ty::Value(val) => {
+                    let tag: &str = val;
+                    match tag {
+                        #( #unit_arms )*
+                        #unknown
+                    }
+                }
+            };
+            if data.is_empty() {
+                quote! {
+                    match gon {
+                        #gon_ty::Object(_) | #gon_ty::Array(_) => std::result::Result::Err(gon_rs::from::FromGonError::ExpectedValue),
+                        #value
+                    }
+                }
+            } else {
+                let data_arms = data.iter().map(|(v, attrs)| {
+                    let names = names(v, attrs);
+                    let content = content(v, attrs)?;
+                    Ok(quote! { #names => #content, })
+                }).collect::<syn::Result<Vec<_>>>()?;
+                quote! {
+                    match gon {
+                        #gon_ty::Object(map) if map.len() == 1 => {
+                            let (tag, gon) = map.iter().next().unwrap();
+                            let tag: &str = tag;
+                            match tag {
+                                #( #data_arms )*
+                                #unknown
                             }
                         }
+                        #gon_ty::Object(map) => std::result::Result::Err(gon_rs::from::FromGonError::InvalidLength { expected: 1, found: map.len() }),
+                        #gon_ty::Array(_) => std::result::Result::Err(gon_rs::from::FromGonError::ExpectedObject),
+                        #value
+                    }
+                }
+            }
+        }
+        Tagging::Internal { tag } => {
+            let arms = variants.iter().map(|(v, attrs)| {
+                let names = names(v, attrs);
+                let ident = &v.ident;
+                let body = match &v.fields {
+                    Fields::Unit => quote!(std::result::Result::Ok(Self::#ident)),
+                    Fields::Unnamed(_) if !is_newtype(v) => {
+                        return Err(syn::Error::new(v.span(), "tuple variants can't be internally tagged"));
+                    }
+                    _ => content(v, attrs)?
+                };
+                Ok(quote! { #names => #body, })
+            }).collect::<syn::Result<Vec<_>>>()?;
+            let tag_value = tag_value(tag);
+            quote! {
+                match gon {
+                    #gon_ty::Object(map) => {
+                        #tag_value
+                        match tag {
+                            #( #arms )*
+                            #unknown
+                        }
                     }
+                    #gon_ty::Array(_) | #gon_ty::Value(_) => std::result::Result::Err(gon_rs::from::FromGonError::ExpectedObject)
                 }
-                Fields::Unit => {
-                    quote! {
-                        match gon {
-                            gon_rs::Gon::Array(_) | gon_rs::Gon::Value(_) => std::result::Result::Err(gon_rs::from::FromGonError::ExpectedObject),
-                            gon_rs::Gon::Object(_) => std::result::Result::Ok(Self)
+            }
+        }
+        Tagging::Adjacent { tag, content: content_key } => {
+            let arms = variants.iter().map(|(v, attrs)| {
+                let names = names(v, attrs);
+                let ident = &v.ident;
+                let body = match &v.fields {
+                    Fields::Unit => quote!(std::result::Result::Ok(Self::#ident)),
+                    _ => {
+                        let content = content(v, attrs)?;
+                        quote! {{
+                            let gon = map.get(#content_key).ok_or(gon_rs::from::FromGonError::Missing(&&#content_key))?;
+                            #content
+                        }}
+                    }
+                };
+                Ok(quote! { #names => #body, })
+            }).collect::<syn::Result<Vec<_>>>()?;
+            let tag_value = tag_value(tag);
+            quote! {
+                match gon {
+                    #gon_ty::Object(map) => {
+                        #tag_value
+                        match tag {
+                            #( #arms )*
+                            #unknown
                         }
                     }
+                    #gon_ty::Array(_) | #gon_ty::Value(_) => std::result::Result::Err(gon_rs::from::FromGonError::ExpectedObject)
                 }
             }
         }
+        Tagging::Untagged => {
+            let attempts = variants.iter().map(|(v, attrs)| {
+                let ident = &v.ident;
+                let body = match &v.fields {
+                    // unit variants are represented by their name like in the other representations
+                    Fields::Unit => {
+                        let names = names(v, attrs);
+                        quote! {
+                            match gon {
+                                #gon_ty::Value(val) if matches!(val.as_str(), #names) => std::result::Result::Ok(Self::#ident),
+                                _ => std::result::Result::Err(gon_rs::from::FromGonError::Unknown)
+                            }
+                        }
+                    }
+                    _ => content(v, attrs)?
+                };
+                Ok(quote! {
+                    let attempt = |mut context: gon_rs::from::Context<'_>| -> std::result::Result<Self, gon_rs::from::FromGonError> { #body };
+                    if let std::result::Result::Ok(val) = context.try_variant(attempt) {
+                        return std::result::Result::Ok(val);
+                    }
+                })
+            }).collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                #( #attempts )*
+                std::result::Result::Err(gon_rs::from::FromGonError::NoMatchingVariant)
+            }
+        }
+    })
+}
+
+fn to_gon(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let container = ContainerAttrs::parse(&input.attrs)?;
+    Ok(match &input.data {
+        Data::Struct(data_struct) => {
+            let (pattern, content) = to_fields(&data_struct.fields, quote!(Self), container.rename_all)?;
+            quote! {
+                let #pattern = self;
+                #content
+            }
+        }
         Data::Enum(data_enum) => {
+            let gon_str = |s: &str| quote!(gon_rs::Gon::Value(gon_rs::GonValue::from(#s)));
+            let object = |entries: proc_macro2::TokenStream| quote! {
+                gon_rs::Gon::Object(std::iter::FromIterator::from_iter([#entries]))
+            };
             let recurse = data_enum.variants.iter().map(|v| {
-                assert!(matches!(v.fields, Fields::Unit), "No enum fields supported for now.");
-                
+                let attrs = VariantAttrs::parse(&v.attrs)?;
+                let name = variant_name(&v.ident, &attrs, container.rename_all);
                 let ident = &v.ident;
-                let str_val = ident.to_string();
-
-                quote! { #str_val => std::result::Result::Ok(Self::#ident), }
-            });
+                let (pattern, content) = if is_newtype(v) {
+                    (quote!(Self::#ident(field0)), quote!(gon_rs::to::ToGon::to_gon(field0)))
+                } else {
+                    to_fields(&v.fields, quote!(Self::#ident), attrs.rename_all)?
+                };
+                let unit = matches!(v.fields, Fields::Unit);
+                let name_val = gon_str(&name);
+                let gon = match &container.tagging {
+                    Tagging::External if unit => name_val,
+                    Tagging::External => object(quote!((std::string::String::from(#name), #content))),
+                    Tagging::Internal { tag } if unit => object(quote!((std::string::String::from(#tag), #name_val))),
+                    Tagging::Internal { tag } => {
+                        if matches!(v.fields, Fields::Unnamed(_)) && !is_newtype(v) {
+                            return Err(syn::Error::new(v.span(), "tuple variants can't be internally tagged"));
+                        }
+                        quote! {{
+                            let mut gon = #content;
+                            if let gon_rs::Gon::Object(map) = &mut gon {
+                                map.shift_insert(0, std::string::String::from(#tag), #name_val);
+                            }
+                            gon
+                        }}
+                    }
+                    Tagging::Adjacent { tag, .. } if unit => object(quote!((std::string::String::from(#tag), #name_val))),
+                    Tagging::Adjacent { tag, content: content_key } => object(quote! {
+                        (std::string::String::from(#tag), #name_val),
+                        (std::string::String::from(#content_key), #content)
+                    }),
+                    Tagging::Untagged if unit => name_val,
+                    Tagging::Untagged => content
+                };
+                Ok(quote! { #pattern => #gon, })
+            }).collect::<syn::Result<Vec<_>>>()?;
 
             quote! {
-                match gon {
-                    gon_rs::Gon::Object(_) | gon_rs::Gon::Array(_) => std::result::Result::Err(gon_rs::from::FromGonError::ExpectedValue),
-                    gon_rs::Gon::Value(val) => match val.as_str() {
-                        #( #recurse )*
-                        _ =>  std::result::Result::Err(gon_rs::from::FromGonError::UnexpectedVariant(val.to_owned()))
-                    }
+                match self {
+                    #( #recurse )*
                 }
             }
         }
-        Data::Union(_) => panic!("No union support for #[derive(FromGon)]"),
-    }
-}
\ No newline at end of file
+        Data::Union(_) => panic!("No union support for #[derive(ToGon)]"),
+    })
+}
+
+/// A pattern binding the fields of a struct or variant by reference and an expression converting them to GON.
+fn to_fields(
+    fields: &Fields,
+    constructor: proc_macro2::TokenStream,
+    rename_all: Option<RenameRule>
+) -> syn::Result<(proc_macro2::TokenStream, proc_macro2::TokenStream)> {
+    Ok(match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|f| &f.ident);
+            let attrs = fields.named.iter().map(|f| FieldAttrs::parse(&f.attrs)).collect::<syn::Result<Vec<_>>>()?;
+            let recurse = fields.named.iter().zip(&attrs).map(|(f, attrs)| {
+                let name = &f.ident;
+                if attrs.flatten {
+                    // the entries of flattened objects are merged into the struct's object
+                    return quote_spanned! {f.span()=>
+                        if let gon_rs::Gon::Object(map) = &mut gon_rs::to::ToGon::to_gon(#name) {
+                            __gon_entries.extend(map.drain(..));
+                        }
+                    };
+                }
+                let name_str = field_name(name.as_ref().unwrap(), attrs, rename_all);
+                quote_spanned! {f.span()=>
+                    __gon_entries.push((std::string::String::from(#name_str), gon_rs::to::ToGon::to_gon(#name)));
+                }
+            });
+            (quote!(#constructor { #( #names ),* }), quote! {{
+                // named so it doesn't shadow the fields, which are bound by their names
+                let mut __gon_entries = std::vec::Vec::new();
+                #( #recurse )*
+                gon_rs::Gon::Object(std::iter::FromIterator::from_iter(__gon_entries))
+            }})
+        }
+        Fields::Unnamed(fields) => {
+            let bindings: Vec<_> = (0..fields.unnamed.len()).map(|i| format_ident!("field{}", i)).collect();
+            let recurse = fields.unnamed.iter().zip(&bindings).map(|(f, binding)| {
+                if FieldAttrs::parse(&f.attrs)?.flatten {
+                    return Err(syn::Error::new(f.span(), "only named fields can be flattened"));
+                }
+                Ok(quote_spanned! {f.span()=>
+                    gon_rs::to::ToGon::to_gon(#binding),
+                })
+            }).collect::<syn::Result<Vec<_>>>()?;
+            (quote!(#constructor(#( #bindings ),*)), quote! {
+                gon_rs::Gon::Array(std::vec![#( #recurse )*])
+            })
+        }
+        Fields::Unit => {
+            (constructor, quote! {
+                gon_rs::Gon::Object(std::default::Default::default())
+            })
+        }
+    })
+}
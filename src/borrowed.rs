@@ -0,0 +1,335 @@
+//! A GON tree borrowing its keys and values from the source text where possible, avoiding an allocation for every
+//! string. Only strings containing escape sequences have to be copied.
+
+use std::{borrow::Cow, collections::HashMap, ops::Index, str::FromStr};
+
+use arrayvec::ArrayVec;
+use indexmap::IndexMap;
+
+use crate::{from::{Context, FromGonError, UnknownField}, parser::{self, Node}, Gon, GonError, GonGetError, GonValue, ParseOptions};
+
+/// A GON tree borrowing unescaped strings from its source. Unlike `Gon`, it is dropped, cloned and converted with
+/// `into_owned` recursively, so it relies on the parser's depth limit.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BorrowedGon<'a> {
+    Object(IndexMap<Cow<'a, str>, BorrowedGon<'a>>),
+    Array(Vec<BorrowedGon<'a>>),
+    Value(GonValue<Cow<'a, str>>)
+}
+
+impl<'s> Node<'s> for BorrowedGon<'s> {
+    type Str = Cow<'s, str>;
+    fn string(s: Cow<'s, str>) -> Cow<'s, str> {
+        s
+    }
+    fn object(map: IndexMap<Cow<'s, str>, Self>) -> Self {
+        Self::Object(map)
+    }
+    fn array(arr: Vec<Self>) -> Self {
+        Self::Array(arr)
+    }
+    fn value(val: Cow<'s, str>, quoted: bool) -> Self {
+        Self::Value(GonValue { text: val, quoted })
+    }
+}
+
+impl<'a> Index<&str> for BorrowedGon<'a> {
+    type Output = BorrowedGon<'a>;
+    fn index(&self, index: &str) -> &Self::Output {
+        match self {
+            Self::Object(map) => &map[index],
+            Self::Array(_) => panic!("Tried to string-index into GON array!"),
+            Self::Value(_) => panic!("Tried to index into GON value!")
+        }
+    }
+}
+
+impl<'a> Index<usize> for BorrowedGon<'a> {
+    type Output = BorrowedGon<'a>;
+    fn index(&self, index: usize) -> &Self::Output {
+        match self {
+            Self::Array(arr) => &arr[index],
+            Self::Value(_) => panic!("Tried to int-index into GON value!"),
+            Self::Object(_) => panic!("Tried to int-index into GON object!")
+        }
+    }
+}
+
+impl<'a> From<BorrowedGon<'a>> for Gon {
+    fn from(gon: BorrowedGon<'a>) -> Self {
+        gon.into_owned()
+    }
+}
+
+impl<'a> BorrowedGon<'a> {
+    /// Parses GON, borrowing all strings that don't contain escape sequences from the source.
+    pub fn parse(s: &'a str) -> Result<Self, GonError> {
+        parser::parse_str(s)
+    }
+
+    /// Parses with the provided options, borrowing strings like `BorrowedGon::parse`.
+    pub fn parse_with(s: &'a str, options: &ParseOptions) -> Result<Self, GonError> {
+        parser::parse_str_with(s, options)
+    }
+
+    /// Converts this into an owned `Gon`, copying all borrowed strings.
+    pub fn into_owned(self) -> Gon {
+        match self {
+            Self::Object(map) => Gon::Object(map.into_iter().map(|(key, val)| (key.into_owned(), val.into_owned())).collect()),
+            Self::Array(arr) => Gon::Array(arr.into_iter().map(Self::into_owned).collect()),
+            Self::Value(val) => Gon::Value(val.into_owned())
+        }
+    }
+
+    /// Tries to get the GON as a value of a specific type that can be converted from a string.
+    /// Will panic on invalid type of object or a conversion fail. Use `try_get`
+    pub fn get<T: FromStr>(&self) -> T {
+        match self {
+            Self::Object(_) => panic!("Tried to get GON object as value!"),
+            Self::Array(_) => panic!("Tried to get GON array as value!"),
+            Self::Value(val) => {
+                match val.parse() {
+                    Ok(val) => val,
+                    Err(_) => panic!("Failed to parse GON value: {}", val)
+                }
+            }
+        }
+    }
+
+    /// Tries to get the GON as a value. In contrast to the `BorrowedGon::get` method, this won't panic and will
+    /// instead return a `Result`.
+    pub fn try_get<T: FromStr>(&self) -> Result<T, GonGetError<<T as FromStr>::Err>> {
+        match self {
+            Self::Object(_) => Err(GonGetError::UnexpectedObject),
+            Self::Array(_) => Err(GonGetError::UnexpectedArray),
+            Self::Value(val) => val.parse().map_err(GonGetError::ConversionFailed)
+        }
+    }
+
+    /// Gets the GON as an object and tries to retrieve a key. If the key isn't present, None is returned.
+    pub fn value(&self, key: &str) -> Option<&BorrowedGon<'a>> {
+        match self {
+            Self::Object(map) => map.get(key),
+            Self::Value(_) => panic!("Tried to string-index into GON value!"),
+            Self::Array(_) => panic!("Tried to string-index into GON array!")
+        }
+    }
+
+    /// Gives a reference to the string if the GON is a string and panics otherwise.
+    pub fn str(&self) -> &str {
+        match self {
+            Self::Object(_) => panic!("Tried to get GON object as str!"),
+            Self::Array(_) => panic!("Tried to get GON array as str!"),
+            Self::Value(val) => val
+        }
+    }
+
+    /// Tries to get the gon as a string value.
+    pub fn try_str(&self) -> Result<&str, FromGonError> {
+        match self {
+            Self::Object(_) | Self::Array(_) => Err(FromGonError::ExpectedValue),
+            Self::Value(val) => Ok(val)
+        }
+    }
+
+    /// Returns the size if the GON is an array and panics otherwise.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Array(arr) => arr.len(),
+            Self::Value(_) => panic!("Tried to int-index into GON value!"),
+            Self::Object(_) => panic!("Tried to int-index into GON object!")
+        }
+    }
+}
+
+/// The borrowing counterpart of `FromGon`. Types can borrow strings from the GON tree for its lifetime.
+pub trait FromBorrowedGon<'a> {
+    fn from_borrowed_gon(gon: &'a BorrowedGon<'a>) -> Result<Self, FromGonError> where Self: Sized;
+
+    /// Like `FromGon::from_gon_with_context`.
+    fn from_borrowed_gon_with_context(gon: &'a BorrowedGon<'a>, _context: Context<'_>) -> Result<Self, FromGonError>
+    where Self: Sized {
+        Self::from_borrowed_gon(gon)
+    }
+
+    /// Like `FromGon::known_keys`. Implemented by the derive macros.
+    #[doc(hidden)]
+    fn known_keys(_keys: &mut Vec<&'static str>) -> bool where Self: Sized {
+        false
+    }
+}
+
+/// Like `from::collect_unknown_fields` for borrowed trees.
+pub fn collect_unknown_fields<'a, T: FromBorrowedGon<'a>>(gon: &'a BorrowedGon<'a>) -> (Result<T, FromGonError>, Vec<UnknownField>) {
+    let mut unknown = Vec::new();
+    let res = T::from_borrowed_gon_with_context(gon, Context::new().collect_unknown_fields(&mut unknown));
+    (res, unknown)
+}
+
+macro_rules! parse_impls {
+    ($($t: ty)*) => {
+        $(
+            impl<'a> FromBorrowedGon<'a> for $t {
+                fn from_borrowed_gon(gon: &'a BorrowedGon<'a>) -> Result<Self, FromGonError> {
+                    match gon {
+                        BorrowedGon::Value(val) => Ok(val.parse::<$t>()?),
+                        BorrowedGon::Object(_) | BorrowedGon::Array(_) => Err(FromGonError::ExpectedValue)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+parse_impls!(u8 u16 u32 u64 u128 i8 i16 i32 i64 i128);
+
+impl<'a> FromBorrowedGon<'a> for &'a str {
+    fn from_borrowed_gon(gon: &'a BorrowedGon<'a>) -> Result<Self, FromGonError> {
+        gon.try_str()
+    }
+}
+
+impl<'a> FromBorrowedGon<'a> for Cow<'a, str> {
+    fn from_borrowed_gon(gon: &'a BorrowedGon<'a>) -> Result<Self, FromGonError> {
+        match gon {
+            BorrowedGon::Value(val) => Ok(val.text.clone()),
+            BorrowedGon::Object(_) | BorrowedGon::Array(_) => Err(FromGonError::ExpectedValue)
+        }
+    }
+}
+
+impl<'a> FromBorrowedGon<'a> for String {
+    fn from_borrowed_gon(gon: &'a BorrowedGon<'a>) -> Result<Self, FromGonError> {
+        gon.try_str().map(str::to_owned)
+    }
+}
+
+impl<'a, T: FromBorrowedGon<'a>, const N: usize> FromBorrowedGon<'a> for [T; N] {
+    fn from_borrowed_gon(gon: &'a BorrowedGon<'a>) -> Result<Self, FromGonError> {
+        Self::from_borrowed_gon_with_context(gon, Context::new())
+    }
+
+    fn from_borrowed_gon_with_context(gon: &'a BorrowedGon<'a>, mut context: Context<'_>) -> Result<Self, FromGonError> {
+        match gon {
+            BorrowedGon::Object(_) | BorrowedGon::Value(_) => Err(FromGonError::ExpectedArray),
+            BorrowedGon::Array(arr) => {
+                if arr.len() != N {
+                    return Err(FromGonError::InvalidLength {
+                        expected: N,
+                        found: arr.len()
+                    })
+                }
+                let array_vec = arr.iter().map(|entry| T::from_borrowed_gon_with_context(entry, context.field())).collect::<Result<ArrayVec<T, N>, _>>()?;
+                // SAFETY: the length is checked to be equal in the if check above. The map also doesn't filter any values.
+                Ok(unsafe { array_vec.into_inner_unchecked() })
+            }
+        }
+    }
+}
+
+impl<'a, T: FromBorrowedGon<'a>> FromBorrowedGon<'a> for Vec<T> {
+    fn from_borrowed_gon(gon: &'a BorrowedGon<'a>) -> Result<Self, FromGonError> {
+        Self::from_borrowed_gon_with_context(gon, Context::new())
+    }
+
+    fn from_borrowed_gon_with_context(gon: &'a BorrowedGon<'a>, mut context: Context<'_>) -> Result<Self, FromGonError> {
+        match gon {
+            BorrowedGon::Object(_) | BorrowedGon::Value(_) => Err(FromGonError::ExpectedArray),
+            BorrowedGon::Array(arr) => arr.iter().map(|entry| T::from_borrowed_gon_with_context(entry, context.field())).collect()
+        }
+    }
+}
+
+impl<'a, T: FromBorrowedGon<'a>> FromBorrowedGon<'a> for Option<T> {
+    fn from_borrowed_gon(gon: &'a BorrowedGon<'a>) -> Result<Self, FromGonError> {
+        match gon {
+            BorrowedGon::Value(val) if val.is_null() => Ok(None),
+            _ => T::from_borrowed_gon(gon).map(Some)
+        }
+    }
+
+    fn from_borrowed_gon_with_context(gon: &'a BorrowedGon<'a>, context: Context<'_>) -> Result<Self, FromGonError> {
+        match gon {
+            BorrowedGon::Value(val) if val.is_null() => Ok(None),
+            _ => T::from_borrowed_gon_with_context(gon, context).map(Some)
+        }
+    }
+
+    fn known_keys(keys: &mut Vec<&'static str>) -> bool {
+        T::known_keys(keys)
+    }
+}
+
+impl<'a> FromBorrowedGon<'a> for BorrowedGon<'a> {
+    fn from_borrowed_gon(gon: &'a BorrowedGon<'a>) -> Result<Self, FromGonError> {
+        Ok(gon.clone())
+    }
+}
+
+impl<'a> FromBorrowedGon<'a> for Gon {
+    fn from_borrowed_gon(gon: &'a BorrowedGon<'a>) -> Result<Self, FromGonError> {
+        Ok(gon.clone().into_owned())
+    }
+}
+
+impl<'a, T: FromBorrowedGon<'a>> FromBorrowedGon<'a> for HashMap<&'a str, T> {
+    fn from_borrowed_gon(gon: &'a BorrowedGon<'a>) -> Result<Self, FromGonError> {
+        Self::from_borrowed_gon_with_context(gon, Context::new())
+    }
+
+    fn from_borrowed_gon_with_context(gon: &'a BorrowedGon<'a>, mut context: Context<'_>) -> Result<Self, FromGonError> {
+        match gon {
+            BorrowedGon::Array(_) | BorrowedGon::Value(_) => Err(FromGonError::ExpectedObject),
+            BorrowedGon::Object(map) => {
+                map.iter().map(|(key, val)| Ok((key.as_ref(), T::from_borrowed_gon_with_context(val, context.field())?))).collect()
+            }
+        }
+    }
+}
+
+impl<'a, T: FromBorrowedGon<'a>> FromBorrowedGon<'a> for IndexMap<&'a str, T> {
+    fn from_borrowed_gon(gon: &'a BorrowedGon<'a>) -> Result<Self, FromGonError> {
+        Self::from_borrowed_gon_with_context(gon, Context::new())
+    }
+
+    fn from_borrowed_gon_with_context(gon: &'a BorrowedGon<'a>, mut context: Context<'_>) -> Result<Self, FromGonError> {
+        match gon {
+            BorrowedGon::Array(_) | BorrowedGon::Value(_) => Err(FromGonError::ExpectedObject),
+            BorrowedGon::Object(map) => {
+                map.iter().map(|(key, val)| Ok((key.as_ref(), T::from_borrowed_gon_with_context(val, context.field())?))).collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{Gon, GonValue};
+    use super::{BorrowedGon, FromBorrowedGon};
+
+    #[test]
+    fn borrows_unescaped() {
+        let src = r#"
+            name "Big Factory"
+            escaped "line\nbreak"
+            bare\tescape 1
+            widgets [8346 854687]
+        "#;
+        let gon = BorrowedGon::parse(src).unwrap();
+        let BorrowedGon::Object(map) = &gon else { panic!("Expected object") };
+        assert!(map.keys().all(|key| matches!(key, Cow::Borrowed(_)) != (key == "bare\tescape")));
+        assert!(matches!(&gon["name"], BorrowedGon::Value(GonValue { text: Cow::Borrowed("Big Factory"), quoted: true })));
+        assert!(matches!(&gon["escaped"], BorrowedGon::Value(GonValue { text: Cow::Owned(s), .. }) if s == "line\nbreak"));
+        assert_eq!(gon["widgets"][1].get::<u32>(), 854687);
+
+        let names: Vec<&str> = Vec::from_borrowed_gon(&gon["widgets"]).unwrap();
+        assert_eq!(names, ["8346", "854687"]);
+        let escaped = <&str>::from_borrowed_gon(&gon["escaped"]).unwrap();
+        assert_eq!(escaped, "line\nbreak");
+
+        assert_eq!(gon.into_owned(), Gon::parse(src).unwrap());
+    }
+}
@@ -0,0 +1,363 @@
+//! serde deserialization from GON, only available with the `serde` feature.
+
+use serde::{
+    de::{self, value::{MapDeserializer, SeqDeserializer}, DeserializeOwned, IntoDeserializer, Visitor},
+    forward_to_deserialize_any, Deserialize
+};
+
+use crate::{from::FromGonError, Gon, GonValue, IndexMap};
+
+/// Parses GON text and deserializes it into a value of type `T`.
+pub fn from_str<T: DeserializeOwned>(s: &str) -> Result<T, FromGonError> {
+    from_gon(&Gon::parse(s)?)
+}
+
+/// Deserializes a parsed GON tree into a value of type `T`. Strings in `T` can borrow from the tree.
+pub fn from_gon<'de, T: Deserialize<'de>>(gon: &'de Gon) -> Result<T, FromGonError> {
+    T::deserialize(gon)
+}
+
+impl de::Error for FromGonError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self::Other(msg.to_string().into())
+    }
+}
+
+impl<'de> IntoDeserializer<'de, FromGonError> for &'de Gon {
+    type Deserializer = Self;
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+fn value(gon: &Gon) -> Result<&str, FromGonError> {
+    match gon {
+        Gon::Value(val) => Ok(val),
+        Gon::Object(_) | Gon::Array(_) => Err(FromGonError::ExpectedValue)
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($value: ident) => {
+        deserialize_parsed!($value;
+            deserialize_i8 visit_i8 i8, deserialize_i16 visit_i16 i16, deserialize_i32 visit_i32 i32,
+            deserialize_i64 visit_i64 i64, deserialize_i128 visit_i128 i128,
+            deserialize_u8 visit_u8 u8, deserialize_u16 visit_u16 u16, deserialize_u32 visit_u32 u32,
+            deserialize_u64 visit_u64 u64, deserialize_u128 visit_u128 u128,
+            deserialize_f32 visit_f32 f32, deserialize_f64 visit_f64 f64
+        );
+
+        fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            match $value(self)? {
+                "true" => visitor.visit_bool(true),
+                "false" => visitor.visit_bool(false),
+                other => Err(FromGonError::Parse(format!("invalid bool: {other}").into()))
+            }
+        }
+
+        fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            let val = $value(self)?;
+            let mut chars = val.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => visitor.visit_char(c),
+                _ => Err(FromGonError::Parse(format!("invalid char: {val}").into()))
+            }
+        }
+    };
+    ($value: ident; $($method: ident $visit: ident $t: ty),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                visitor.$visit($value(self)?.parse::<$t>()?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for &'de Gon {
+    type Error = FromGonError;
+
+    /// Values are typed the same way `Serialize for Gon` types them, so self-describing formats like `flatten` and
+    /// `untagged` see numbers, bools and null instead of strings.
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Gon::Value(val) if val.is_null() => visitor.visit_unit(),
+            Gon::Value(val) if val.is_bool() => visitor.visit_bool(val.as_str() == "true"),
+            Gon::Value(val) if val.is_int() => match (val.parse::<i64>(), val.parse::<u64>()) {
+                (Ok(i), _) => visitor.visit_i64(i),
+                (_, Ok(u)) => visitor.visit_u64(u),
+                _ => visitor.visit_borrowed_str(val)
+            }
+            Gon::Value(val) if val.is_float() => match val.parse::<f64>() {
+                Ok(f) if f.is_finite() => visitor.visit_f64(f),
+                _ => visitor.visit_borrowed_str(val)
+            }
+            Gon::Value(val) => visitor.visit_borrowed_str(val),
+            Gon::Array(_) => self.deserialize_seq(visitor),
+            Gon::Object(_) => self.deserialize_map(visitor)
+        }
+    }
+
+    deserialize_parsed!(value);
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(value(self)?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Gon::Value(val) => visitor.visit_borrowed_bytes(val.as_bytes()),
+            Gon::Array(_) => self.deserialize_seq(visitor),
+            Gon::Object(_) => Err(FromGonError::UnexpectedObject)
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    /// GON has no null value, so the JSON `null` is used for `None`. A quoted `"null"` is still a string.
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Gon::Value(val) if val.is_null() => visitor.visit_none(),
+            _ => visitor.visit_some(self)
+        }
+    }
+
+    /// Units are represented as empty objects, just like unit structs in `FromGon`.
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Gon::Object(_) => visitor.visit_unit(),
+            Gon::Array(_) | Gon::Value(_) => Err(FromGonError::ExpectedObject)
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Gon::Array(arr) => {
+                let mut seq = SeqDeserializer::new(arr.iter());
+                let val = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(val)
+            }
+            Gon::Object(_) | Gon::Value(_) => Err(FromGonError::ExpectedArray)
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Gon::Object(map) => {
+                let mut map = MapDeserializer::new(map.iter().map(|(key, val)| (Key(key), val)));
+                let val = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(val)
+            }
+            Gon::Array(_) | Gon::Value(_) => Err(FromGonError::ExpectedObject)
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    /// Enums are externally tagged: unit variants are plain values and variants with data are objects with a single
+    /// key naming the variant.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        match self {
+            Gon::Value(val) => visitor.visit_enum(de::value::BorrowedStrDeserializer::new(val)),
+            Gon::Object(map) if map.len() == 1 => {
+                let (variant, content) = map.iter().next().unwrap();
+                visitor.visit_enum(EnumDeserializer { variant, content })
+            }
+            Gon::Object(map) => Err(FromGonError::InvalidLength { expected: 1, found: map.len() }),
+            Gon::Array(_) => Err(FromGonError::UnexpectedArray)
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! { identifier }
+}
+
+/// Deserializes object keys, which can be parsed into numbers and other values.
+struct Key<'de>(&'de str);
+
+fn key(key: Key<'_>) -> Result<&str, FromGonError> {
+    Ok(key.0)
+}
+
+impl<'de> IntoDeserializer<'de, FromGonError> for Key<'de> {
+    type Deserializer = Self;
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Key<'de> {
+    type Error = FromGonError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    deserialize_parsed!(key);
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(de::value::BorrowedStrDeserializer::new(self.0))
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct EnumDeserializer<'de> {
+    variant: &'de str,
+    content: &'de Gon
+}
+impl<'de> de::EnumAccess<'de> for EnumDeserializer<'de> {
+    type Error = FromGonError;
+    type Variant = &'de Gon;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant = seed.deserialize(de::value::BorrowedStrDeserializer::<FromGonError>::new(self.variant))?;
+        Ok((variant, self.content))
+    }
+}
+impl<'de> de::VariantAccess<'de> for &'de Gon {
+    type Error = FromGonError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Gon {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(GonVisitor)
+    }
+}
+
+struct GonVisitor;
+
+macro_rules! visit_display {
+    ($($method: ident $t: ty),*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $t) -> Result<Gon, E> {
+                Ok(Gon::Value(v.to_string().into()))
+            }
+        )*
+    };
+}
+
+impl<'de> Visitor<'de> for GonVisitor {
+    type Value = Gon;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("any GON value, array or object")
+    }
+
+    visit_display!(
+        visit_bool bool, visit_i64 i64, visit_i128 i128, visit_u64 u64, visit_u128 u128
+    );
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Gon, E> {
+        Ok(Gon::Value(crate::ser::float_text(v).into()))
+    }
+
+    fn visit_char<E: de::Error>(self, v: char) -> Result<Gon, E> {
+        Ok(Gon::Value(GonValue::string(v.to_string())))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Gon, E> {
+        Ok(Gon::Value(GonValue::string(v.to_owned())))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Gon, E> {
+        Ok(Gon::Value(GonValue::string(v)))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Gon, E> {
+        Ok(Gon::Value("null".into()))
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Gon, D::Error> {
+        Gon::deserialize(deserializer)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Gon, E> {
+        Ok(Gon::Value("null".into()))
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Gon, A::Error> {
+        let mut arr = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(val) = seq.next_element()? {
+            arr.push(val);
+        }
+        Ok(Gon::Array(arr))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut access: A) -> Result<Gon, A::Error> {
+        let mut map = IndexMap::with_capacity(access.size_hint().unwrap_or(0));
+        while let Some((key, val)) = access.next_entry()? {
+            map.insert(key, val);
+        }
+        Ok(Gon::Object(map))
+    }
+}
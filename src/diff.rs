@@ -0,0 +1,298 @@
+//! Structured differences between two GON trees, addressed by path instead of by line.
+//! A `Diff` can be applied to another GON to repeat the same changes.
+
+use crate::{path::{PathError, PathErrorKind}, writer::{self, WriteStyle}, Gon};
+
+/// How arrays are compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayDiff {
+    /// Elements are compared at the same index, so inserting an element changes all following ones.
+    #[default]
+    ByIndex,
+    /// Elements are aligned using the longest common subsequence, so insertions and removals are detected.
+    Lcs
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added(Gon),
+    Removed(Gon),
+    Changed { from: Gon, to: Gon }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffEntry {
+    pub path: Vec<PathSegment>,
+    pub change: Change
+}
+impl DiffEntry {
+    /// The path of the entry as a JSON Pointer, like `/big_factory/locations/2`.
+    pub fn pointer(&self) -> String {
+        pointer(&self.path)
+    }
+}
+
+pub(crate) fn pointer(path: &[PathSegment]) -> String {
+    path.iter().map(|segment| match segment {
+        PathSegment::Key(key) => format!("/{}", key.replace('~', "~0").replace('/', "~1")),
+        PathSegment::Index(index) => format!("/{index}")
+    }).collect()
+}
+
+/// The changes between two GON trees.
+///
+/// Within an array, changed elements are listed first, addressed by their old index. Removed elements follow in
+/// descending order of their old index and added elements in ascending order of their new index. This way the entries
+/// can be applied one after another.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Diff {
+    pub entries: Vec<DiffEntry>
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Applies all changes to the GON. Values are compared by path only, so the GON doesn't have to be the one the
+    /// diff was created from: changed keys are inserted if they are missing. Fails if a path can't be resolved.
+    pub fn apply(&self, gon: &mut Gon) -> Result<(), PathError> {
+        for entry in &self.entries {
+            let Some((last, parent_path)) = entry.path.split_last() else {
+                if let Change::Changed { to, .. } | Change::Added(to) = &entry.change {
+                    *gon = to.clone();
+                }
+                continue;
+            };
+            let parent_pointer = pointer(parent_path);
+            let parent = gon.try_pointer_mut(&parent_pointer)?;
+            let err = |kind| PathError { path: entry.pointer(), segment: parent_path.len(), kind };
+            match (last, &entry.change) {
+                (PathSegment::Key(key), Change::Added(val) | Change::Changed { to: val, .. }) => {
+                    parent.try_insert(key.clone(), val.clone()).map_err(|e| err(e.into()))?;
+                }
+                (PathSegment::Key(key), Change::Removed(_)) => {
+                    parent.try_remove(key)
+                        .map_err(|e| err(e.into()))?
+                        .ok_or_else(|| err(PathErrorKind::MissingKey(key.clone())))?;
+                }
+                (PathSegment::Index(index), Change::Added(val)) => {
+                    parent.try_insert_at(*index, val.clone()).map_err(|e| err(e.into()))?;
+                }
+                (PathSegment::Index(index), Change::Changed { to, .. }) => {
+                    *parent.try_index_mut(*index).map_err(|e| err(e.into()))? = to.clone();
+                }
+                (PathSegment::Index(index), Change::Removed(_)) => {
+                    parent.try_remove_at(*index).map_err(|e| err(e.into()))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lists one change per line: `+ /path value`, `- /path value` or `~ /path old -> new`.
+impl std::fmt::Display for Diff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.entries {
+            match &entry.change {
+                Change::Added(val) => {
+                    write!(f, "+ {} ", entry.pointer())?;
+                    writer::write_value_to(f, val, WriteStyle::Compact)?;
+                }
+                Change::Removed(val) => {
+                    write!(f, "- {} ", entry.pointer())?;
+                    writer::write_value_to(f, val, WriteStyle::Compact)?;
+                }
+                Change::Changed { from, to } => {
+                    write!(f, "~ {} ", entry.pointer())?;
+                    writer::write_value_to(f, from, WriteStyle::Compact)?;
+                    f.write_str(" -> ")?;
+                    writer::write_value_to(f, to, WriteStyle::Compact)?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Gon {
+    /// Computes the changes from `a` to `b`, comparing arrays by index.
+    pub fn diff(a: &Gon, b: &Gon) -> Diff {
+        Self::diff_with(a, b, ArrayDiff::ByIndex)
+    }
+
+    /// Computes the changes from `a` to `b`, comparing arrays as specified.
+    pub fn diff_with(a: &Gon, b: &Gon, arrays: ArrayDiff) -> Diff {
+        let mut differ = Differ { arrays, path: Vec::new(), entries: Vec::new() };
+        differ.diff(a, b);
+        Diff { entries: differ.entries }
+    }
+}
+
+struct Differ {
+    arrays: ArrayDiff,
+    path: Vec<PathSegment>,
+    entries: Vec<DiffEntry>
+}
+impl Differ {
+    fn push(&mut self, segment: Option<PathSegment>, change: Change) {
+        let mut path = self.path.clone();
+        path.extend(segment);
+        self.entries.push(DiffEntry { path, change });
+    }
+
+    fn nested(&mut self, segment: PathSegment, a: &Gon, b: &Gon) {
+        self.path.push(segment);
+        self.diff(a, b);
+        self.path.pop();
+    }
+
+    fn diff(&mut self, a: &Gon, b: &Gon) {
+        match (a, b) {
+            (Gon::Object(a_map), Gon::Object(b_map)) => {
+                for (key, a_val) in a_map {
+                    match b_map.get(key) {
+                        Some(b_val) => self.nested(PathSegment::Key(key.clone()), a_val, b_val),
+                        None => self.push(Some(PathSegment::Key(key.clone())), Change::Removed(a_val.clone()))
+                    }
+                }
+                for (key, b_val) in b_map {
+                    if !a_map.contains_key(key) {
+                        self.push(Some(PathSegment::Key(key.clone())), Change::Added(b_val.clone()));
+                    }
+                }
+            }
+            (Gon::Array(a_arr), Gon::Array(b_arr)) => {
+                let (changed, removed, added) = match self.arrays {
+                    ArrayDiff::ByIndex => {
+                        let common = a_arr.len().min(b_arr.len());
+                        ((0..common).map(|i| (i, i)).collect(), (common..a_arr.len()).collect(), (common..b_arr.len()).collect())
+                    }
+                    ArrayDiff::Lcs => lcs_alignment(a_arr, b_arr)
+                };
+                for (i, j) in changed {
+                    self.nested(PathSegment::Index(i), &a_arr[i], &b_arr[j]);
+                }
+                for &i in removed.iter().rev() {
+                    self.push(Some(PathSegment::Index(i)), Change::Removed(a_arr[i].clone()));
+                }
+                for j in added {
+                    self.push(Some(PathSegment::Index(j)), Change::Added(b_arr[j].clone()));
+                }
+            }
+            (a, b) if a != b => self.push(None, Change::Changed { from: a.clone(), to: b.clone() }),
+            _ => ()
+        }
+    }
+}
+
+/// Aligns two arrays using their longest common subsequence. Returns the pairs of indices that are compared further,
+/// the indices removed from `a` and the indices added in `b`. Elements that are removed and added at the same
+/// position are paired up so they are diffed recursively.
+fn lcs_alignment(a: &[Gon], b: &[Gon]) -> (Vec<(usize, usize)>, Vec<usize>, Vec<usize>) {
+    // lengths[i][j] is the length of the longest common subsequence of a[i..] and b[j..]
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i] == b[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let (mut changed, mut removed, mut added) = (Vec::new(), Vec::new(), Vec::new());
+    let (mut gap_removed, mut gap_added) = (Vec::new(), Vec::new());
+    let mut flush_gap = |gap_removed: &mut Vec<usize>, gap_added: &mut Vec<usize>| {
+        let paired = gap_removed.len().min(gap_added.len());
+        changed.extend(gap_removed.iter().copied().zip(gap_added.iter().copied()));
+        removed.extend(gap_removed.drain(..).skip(paired));
+        added.extend(gap_added.drain(..).skip(paired));
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            flush_gap(&mut gap_removed, &mut gap_added);
+            i += 1;
+            j += 1;
+        } else if j == b.len() || (i < a.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            gap_removed.push(i);
+            i += 1;
+        } else {
+            gap_added.push(j);
+            j += 1;
+        }
+    }
+    flush_gap(&mut gap_removed, &mut gap_added);
+    (changed, removed, added)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Gon;
+    use super::{ArrayDiff, Change, PathSegment};
+
+    #[test]
+    fn diff_objects() {
+        let a = Gon::parse("sword { damage 5 cost 10 } shield { block 3 } name Items").unwrap();
+        let b = Gon::parse("sword { damage 7 cost 10 } bow { damage 4 } name Items").unwrap();
+        let diff = Gon::diff(&a, &b);
+        assert_eq!(diff.to_string(), "\
+            ~ /sword/damage 5 -> 7\n\
+            - /shield {block 3}\n\
+            + /bow {damage 4}\n\
+        ");
+        assert_eq!(diff.entries[0].path, [PathSegment::Key("sword".to_owned()), PathSegment::Key("damage".to_owned())]);
+        assert!(Gon::diff(&a, &a).is_empty());
+
+        let mut patched = a.clone();
+        diff.apply(&mut patched).unwrap();
+        assert_eq!(patched, b);
+
+        // the same diff can be applied to a different file
+        let mut other = Gon::parse("sword { damage 1 } shield { block 1 } extra 1").unwrap();
+        diff.apply(&mut other).unwrap();
+        assert_eq!(other, Gon::parse("sword { damage 7 } extra 1 bow { damage 4 }").unwrap());
+        assert!(diff.apply(&mut Gon::parse("shield 1").unwrap()).is_err());
+    }
+
+    #[test]
+    fn diff_arrays() {
+        let a = Gon::parse("[a b c { x 1 } d]").unwrap();
+        let b = Gon::parse("[z a c { x 2 } d e]").unwrap();
+
+        let by_index = Gon::diff(&a, &b);
+        assert_eq!(by_index.entries.len(), 4);
+        assert_eq!(by_index.entries[0].change, Change::Changed { from: Gon::Value("a".into()), to: Gon::Value("z".into()) });
+
+        let lcs = Gon::diff_with(&a, &b, ArrayDiff::Lcs);
+        assert_eq!(lcs.to_string(), "\
+            ~ /3/x 1 -> 2\n\
+            - /1 b\n\
+            + /0 z\n\
+            + /5 e\n\
+        ");
+
+        for diff in [by_index, lcs] {
+            let mut patched = a.clone();
+            diff.apply(&mut patched).unwrap();
+            assert_eq!(patched, b);
+            let mut reverted = b.clone();
+            Gon::diff_with(&b, &a, ArrayDiff::Lcs).apply(&mut reverted).unwrap();
+            assert_eq!(reverted, a);
+        }
+
+        let mut root = Gon::Value("x".into());
+        Gon::diff(&root, &a).apply(&mut root).unwrap();
+        assert_eq!(root, a);
+    }
+}
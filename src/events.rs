@@ -0,0 +1,427 @@
+//! A pull parser producing a stream of events instead of a tree, so large sources can be filtered or aggregated
+//! without building the whole `Gon`. `Gon::parse` builds its tree from the same events.
+//!
+//! The events of `a 1 b [x]` are `StartObject`, `Key("a")`, `Value("1")`, `Key("b")`, `StartArray`, `Value("x")`,
+//! `EndArray` and `EndObject`. A top-level object without braces produces the same events as one with braces.
+
+use std::{borrow::Cow, io::{BufReader, Read}};
+
+use crate::{parser::{Parser, ReadParser, StrParser}, Dialect, GonError, GonErrorKind, GonValue, ParseOptions, Position};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'s> {
+    StartObject,
+    /// A key of the current object. The events of its value follow.
+    Key(Cow<'s, str>),
+    EndObject,
+    StartArray,
+    EndArray,
+    Value(GonValue<Cow<'s, str>>)
+}
+
+/// The events of a GON source string. Keys and values are borrowed from the source unless they contain escape
+/// sequences. The iterator ends after the first error.
+pub struct Events<'s> {
+    inner: EventParser<'s, StrParser<'s>>
+}
+impl<'s> Events<'s> {
+    pub fn new(s: &'s str) -> Self {
+        Self::with_options(s, &ParseOptions::default())
+    }
+
+    pub fn with_options(s: &'s str, options: &ParseOptions) -> Self {
+        Self { inner: EventParser::new(StrParser::with_options(s, *options)) }
+    }
+
+    /// The position in the source where the last returned event starts.
+    pub fn pos(&self) -> Position {
+        self.inner.pos()
+    }
+}
+impl<'s> Iterator for Events<'s> {
+    type Item = Result<Event<'s>, GonError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_event().transpose()
+    }
+}
+
+/// The events of GON read from a reader, which is buffered internally. The iterator ends after the first error.
+pub struct ReaderEvents<R> {
+    inner: EventParser<'static, ReadParser<BufReader<R>>>
+}
+impl<R: Read> ReaderEvents<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, &ParseOptions::default())
+    }
+
+    pub fn with_options(reader: R, options: &ParseOptions) -> Self {
+        Self { inner: EventParser::new(ReadParser::with_options(BufReader::new(reader), *options)) }
+    }
+
+    /// The position in the source where the last returned event starts.
+    pub fn pos(&self) -> Position {
+        self.inner.pos()
+    }
+}
+impl<R: Read> Iterator for ReaderEvents<R> {
+    type Item = Result<Event<'static>, GonError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_event().transpose()
+    }
+}
+
+/// What the event parser expects next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Start,
+    /// A key or the end of the current object
+    Key,
+    /// The value of the last key
+    Value,
+    /// An element or the end of the current array
+    Element,
+    /// A separator after a value
+    AfterValue,
+    /// The end of the source
+    End,
+    Done
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Object,
+    Array,
+    /// A top-level object without braces, which ends with the source
+    TopObject
+}
+
+/// Produces events from a parser without recursion. Objects and arrays that are currently open are kept on a stack.
+pub(crate) struct EventParser<'s, P> {
+    pub(crate) parser: P,
+    stack: Vec<Container>,
+    state: State,
+    /// An event that was produced along with the previous one
+    pending: Option<Event<'s>>,
+    pos: Position
+}
+impl<'s, P: Parser<'s>> EventParser<'s, P> {
+    pub(crate) fn new(parser: P) -> Self {
+        Self { parser, stack: Vec::new(), state: State::Start, pending: None, pos: Position::default() }
+    }
+
+    pub(crate) fn pos(&self) -> Position {
+        self.pos
+    }
+
+    /// Returns the next event or `None` at the end of the source. No more events are produced after an error.
+    pub(crate) fn next_event(&mut self) -> Result<Option<Event<'s>>, GonError> {
+        let res = self.step();
+        // errors of the underlying source end the input early, so they take precedence over any parse errors
+        if let Some(err) = self.parser.take_error() {
+            self.state = State::Done;
+            return Err(err);
+        }
+        if res.is_err() {
+            self.state = State::Done;
+        }
+        res
+    }
+
+    fn step(&mut self) -> Result<Option<Event<'s>>, GonError> {
+        if let Some(event) = self.pending.take() {
+            return Ok(Some(event));
+        }
+        loop {
+            let res = match self.state {
+                State::Start => self.start(),
+                State::Key => self.key(),
+                State::Value => self.value(),
+                State::Element => self.element(),
+                State::AfterValue => self.after_value().map(|()| None),
+                State::End => self.end().map(|()| None),
+                State::Done => return Ok(None)
+            };
+            match res {
+                Ok(Some(event)) => return Ok(Some(event)),
+                Ok(None) => (),
+                Err(err) => self.recover(err)?
+            }
+        }
+    }
+
+    /// Resumes at the next key or element of the current object or array in recovering mode. Errors in the JSON
+    /// dialect or outside of any object or array end parsing.
+    fn recover(&mut self, err: GonError) -> Result<(), GonError> {
+        if self.parser.options().dialect == Dialect::Json {
+            return Err(err);
+        }
+        let (closing, state) = match self.stack.last() {
+            None => return Err(err),
+            Some(Container::Array) => (']', State::Element),
+            Some(Container::Object | Container::TopObject) => ('}', State::Key)
+        };
+        self.parser.recover(err, closing)?;
+        self.state = state;
+        Ok(())
+    }
+
+    /// Starts the document. The outermost braces of an object are optional and a single value is also valid.
+    fn start(&mut self) -> Result<Option<Event<'s>>, GonError> {
+        self.parser.skip_whitespace();
+        self.pos = self.parser.pos();
+        let options = self.parser.options();
+        let braceless = options.dialect == Dialect::Gon && options.optional_top_level_braces;
+        match self.parser.peek() {
+            Some('{' | '[') => self.value(),
+            _ if !braceless => self.value(),
+            None => {
+                self.parser.count_node()?;
+                self.pending = Some(Event::EndObject);
+                self.state = State::End;
+                Ok(Some(Event::StartObject))
+            }
+            Some(c) => {
+                // Either the first key of an object without braces or a single value if nothing follows it.
+                self.parser.count_node()?;
+                let first = match self.parser.parse_string() {
+                    Ok(first) => {
+                        self.parser.skip_whitespace();
+                        if self.parser.peek().is_none() {
+                            self.state = State::End;
+                            return Ok(Some(Event::Value(GonValue { text: first, quoted: c == '"' })));
+                        }
+                        Some(first)
+                    }
+                    Err(err) => {
+                        self.parser.recover(err, '}')?;
+                        None
+                    }
+                };
+                self.parser.enter()?;
+                self.stack.push(Container::TopObject);
+                self.state = State::Key;
+                if let Some(first) = first {
+                    self.parser.skip_whitespace_and_token(':');
+                    self.pending = Some(Event::Key(first));
+                    self.state = State::Value;
+                }
+                Ok(Some(Event::StartObject))
+            }
+        }
+    }
+
+    fn key(&mut self) -> Result<Option<Event<'s>>, GonError> {
+        self.pos = self.parser.pos();
+        let json = self.parser.options().dialect == Dialect::Json;
+        let top_level = self.stack.last() == Some(&Container::TopObject);
+        match self.parser.peek() {
+            Some('}') if top_level => {
+                // an object without braces can't be closed, so in recovering mode parsing goes on after a stray brace
+                let err = self.parser.unexpected(GonErrorKind::EndOfFileExpected);
+                self.parser.record(err)?;
+                self.parser.next();
+                self.parser.skip_whitespace();
+                Ok(None)
+            }
+            Some('}') => {
+                self.parser.next();
+                Ok(Some(self.end_container()))
+            }
+            None if top_level => Ok(Some(self.end_container())),
+            None => {
+                let err = self.parser.error(GonErrorKind::ClosingBraceExpected);
+                self.parser.record(err)?;
+                Ok(Some(self.end_container()))
+            }
+            Some(c) if json && c != '"' => Err(self.parser.unexpected(GonErrorKind::UnquotedString)),
+            Some(_) => {
+                let key = self.parser.parse_string()?;
+                if json {
+                    self.parser.skip_whitespace();
+                    if self.parser.peek() != Some(':') {
+                        return Err(self.parser.unexpected(GonErrorKind::ColonExpected));
+                    }
+                    self.parser.next();
+                    self.parser.skip_whitespace();
+                } else {
+                    self.parser.skip_whitespace_and_token(':');
+                }
+                self.state = State::Value;
+                Ok(Some(Event::Key(key)))
+            }
+        }
+    }
+
+    /// Parses a value or the start of an object or array.
+    fn value(&mut self) -> Result<Option<Event<'s>>, GonError> {
+        self.pos = self.parser.pos();
+        self.parser.count_node()?;
+        let json = self.parser.options().dialect == Dialect::Json;
+        let (text, quoted) = match self.parser.peek() {
+            Some('{') => {
+                self.parser.enter()?;
+                self.parser.next();
+                self.parser.skip_whitespace();
+                self.stack.push(Container::Object);
+                self.state = State::Key;
+                return Ok(Some(Event::StartObject));
+            }
+            Some('[') => {
+                self.parser.enter()?;
+                self.parser.next();
+                self.parser.skip_whitespace();
+                self.stack.push(Container::Array);
+                self.state = State::Element;
+                return Ok(Some(Event::StartArray));
+            }
+            None => return Err(self.parser.error(GonErrorKind::ValueExpected)),
+            Some(c) if json && c != '"' => (self.parser.parse_json_literal()?, false),
+            Some('}' | ']' | ':' | ',') => return Err(self.parser.error(GonErrorKind::ValueExpected)),
+            Some(c) => (self.parser.parse_string()?, c == '"')
+        };
+        self.state = State::AfterValue;
+        Ok(Some(Event::Value(GonValue { text, quoted })))
+    }
+
+    fn element(&mut self) -> Result<Option<Event<'s>>, GonError> {
+        match self.parser.peek() {
+            Some(']') => {
+                self.pos = self.parser.pos();
+                self.parser.next();
+                Ok(Some(self.end_container()))
+            }
+            None => {
+                self.pos = self.parser.pos();
+                let err = self.parser.error(GonErrorKind::ClosingBracketExpected);
+                self.parser.record(err)?;
+                Ok(Some(self.end_container()))
+            }
+            Some(_) => self.value()
+        }
+    }
+
+    /// Leaves the current object or array.
+    fn end_container(&mut self) -> Event<'s> {
+        self.parser.leave();
+        self.state = State::AfterValue;
+        match self.stack.pop() {
+            Some(Container::Array) => Event::EndArray,
+            _ => Event::EndObject
+        }
+    }
+
+    /// Skips the separator after a value. In JSON, it is required unless the object or array ends.
+    fn after_value(&mut self) -> Result<(), GonError> {
+        let (closing, state) = match self.stack.last() {
+            None => {
+                self.state = State::End;
+                return Ok(());
+            }
+            Some(Container::Array) => (']', State::Element),
+            Some(Container::Object | Container::TopObject) => ('}', State::Key)
+        };
+        self.state = state;
+        if self.parser.options().dialect == Dialect::Gon {
+            self.parser.skip_whitespace_and_token(',');
+            return Ok(());
+        }
+        self.parser.skip_whitespace();
+        match self.parser.peek() {
+            Some(',') => {
+                self.parser.next();
+                self.parser.skip_whitespace();
+                if self.parser.peek() == Some(closing) {
+                    return Err(self.parser.error(GonErrorKind::TrailingComma));
+                }
+                Ok(())
+            }
+            Some(c) if c == closing => Ok(()),
+            // reported as a missing closing brace or bracket
+            None => Ok(()),
+            Some(_) => Err(self.parser.unexpected(GonErrorKind::CommaExpected))
+        }
+    }
+
+    fn end(&mut self) -> Result<(), GonError> {
+        self.state = State::Done;
+        self.parser.skip_whitespace();
+        if self.parser.peek().is_some() {
+            let err = self.parser.unexpected(GonErrorKind::EndOfFileExpected);
+            self.parser.record(err)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{GonErrorKind, GonValue, ParseOptions};
+    use super::{Event, Events, ReaderEvents};
+
+    fn events(s: &str) -> Vec<Event<'_>> {
+        Events::new(s).collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn events_of_sources() {
+        let key = |k: &'static str| Event::Key(Cow::Borrowed(k));
+        let val = |v: &'static str| Event::Value(GonValue::bare(Cow::Borrowed(v)));
+        let expected = [
+            Event::StartObject, key("a"), val("1"), key("b"), Event::StartArray, val("x"), Event::StartObject,
+            Event::EndObject, Event::EndArray, Event::EndObject
+        ];
+        assert_eq!(events("a 1 b [x {}]"), expected);
+        assert_eq!(events("{ a: 1, b: [x, {}] }"), expected);
+        assert_eq!(events(r#"{"a": 1, "b": ["x", {}]}"#)[5], Event::Value(GonValue { text: Cow::Borrowed("x"), quoted: true }));
+        assert_eq!(events(""), [Event::StartObject, Event::EndObject]);
+        assert_eq!(events("  value "), [val("value")]);
+        assert_eq!(events(r#""esc\"aped""#), [Event::Value(GonValue { text: Cow::Owned("esc\"aped".to_owned()), quoted: true })]);
+
+        let read: Vec<_> = ReaderEvents::new("a 1 b [x {}]".as_bytes()).collect::<Result<_, _>>().unwrap();
+        assert_eq!(read, expected);
+    }
+
+    #[test]
+    fn aggregate() {
+        // sums the prices of all items without building a tree
+        let src = "items [{ name a price 3 } { name b price 4 tags [price] }] price 100";
+        let mut depth = 0;
+        let mut in_price = false;
+        let mut sum = 0;
+        for event in Events::new(src) {
+            match event.unwrap() {
+                Event::StartObject | Event::StartArray => depth += 1,
+                Event::EndObject | Event::EndArray => depth -= 1,
+                Event::Key(key) => in_price = depth == 3 && key == "price",
+                Event::Value(val) if in_price => sum += val.parse::<i32>().unwrap(),
+                Event::Value(_) => ()
+            }
+        }
+        assert_eq!(sum, 7);
+    }
+
+    #[test]
+    fn positions_and_errors() {
+        let mut events = Events::new("a {\n  b \"c\"\n}");
+        let mut positions = Vec::new();
+        while let Some(event) = events.next() {
+            event.unwrap();
+            positions.push((events.pos().line, events.pos().column));
+        }
+        assert_eq!(positions, [(1, 1), (1, 1), (1, 3), (2, 3), (2, 5), (3, 1), (3, 2)]);
+
+        let mut events = Events::new("a [1 }");
+        assert_eq!(events.by_ref().filter_map(Result::ok).count(), 4);
+        let mut events = Events::new("a [1 }");
+        let err = events.find_map(Result::err).unwrap();
+        assert!(matches!(err.kind, GonErrorKind::ValueExpected));
+        assert_eq!(err.pos.offset, 5);
+        assert!(events.next().is_none());
+
+        let json: Vec<_> = Events::with_options("[1, 2,]", &ParseOptions::json()).collect();
+        assert_eq!(json.len(), 4);
+        assert!(matches!(json[3], Err(ref err) if matches!(err.kind, GonErrorKind::TrailingComma)));
+    }
+}
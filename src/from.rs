@@ -1,8 +1,9 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
 use arrayvec::ArrayVec;
+use indexmap::IndexMap;
 
-use crate::{Gon, GonGetError, GonError};
+use crate::{Gon, GonGetError, GonError, ParseOptions};
 
 #[derive(Debug)]
 pub enum FromGonError {
@@ -21,6 +22,12 @@ pub enum FromGonError {
     UnexpectedArray,
     UnexpectedObject,
     UnexpectedVariant(String),
+    /// None of the variants of an untagged enum could be converted
+    NoMatchingVariant,
+    /// A key that isn't read by a struct with `#[gon(deny_unknown_fields)]`
+    UnknownField(UnknownField),
+    /// Derived conversions were nested deeper than the limit of their `Context`
+    DepthLimitExceeded(usize),
     Other(Box<dyn std::error::Error>),
     Unknown
 }
@@ -59,8 +66,185 @@ impl<E: std::error::Error + 'static> From<GonGetError<E>> for FromGonError {
     }
 }
 
+/// The state of a conversion, which derived impls pass on to the conversions of their fields. Derived conversions
+/// are limited to `ParseOptions::DEFAULT_MAX_DEPTH` levels by default, the same as the parser, so trees parsed with a
+/// larger `ParseOptions::max_depth` need the same limit to be converted:
+/// `Config::from_gon_with_context(&gon, Context::new().max_depth(options.max_depth))`.
+#[derive(Debug)]
+pub struct Context<'a> {
+    depth: usize,
+    max_depth: Option<usize>,
+    /// If the object belongs to a struct this is flattened into, which checks its keys instead
+    flattened: bool,
+    /// Where unknown fields are collected, if they aren't ignored
+    unknown: Option<&'a mut Vec<UnknownField>>,
+    /// The key of the tag of an internally tagged enum, which is part of the object of a newtype variant's content
+    tag: Option<&'static str>
+}
+impl Default for Context<'_> {
+    fn default() -> Self {
+        Self { depth: 0, max_depth: Some(ParseOptions::DEFAULT_MAX_DEPTH), flattened: false, unknown: None, tag: None }
+    }
+}
+impl<'a> Context<'a> {
+    /// The default context, which is the same `from_gon` uses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the nesting of derived conversions, which recurse for every nested struct. `None` removes the limit,
+    /// which can overflow the stack on deeply nested trees.
+    pub fn max_depth(self, max_depth: Option<usize>) -> Self {
+        Self { max_depth, ..self }
+    }
+
+    /// Collects the keys that derived structs don't read into `unknown` instead of ignoring them. Structs with
+    /// `#[gon(deny_unknown_fields)]` still fail on unknown keys.
+    pub fn collect_unknown_fields(self, unknown: &'a mut Vec<UnknownField>) -> Self {
+        Self { unknown: Some(unknown), ..self }
+    }
+
+    /// Enters a derived conversion, failing if it is nested deeper than the limit. Used by the code generated by the
+    /// derive macros.
+    #[doc(hidden)]
+    pub fn enter(self) -> Result<Self, FromGonError> {
+        if let Some(max) = self.max_depth.filter(|max| self.depth >= *max) {
+            return Err(FromGonError::DepthLimitExceeded(max));
+        }
+        Ok(Self { depth: self.depth + 1, ..self })
+    }
+
+    /// The context of a field or element, which is converted from its own object or value.
+    #[doc(hidden)]
+    pub fn field(&mut self) -> Context<'_> {
+        Context {
+            depth: self.depth,
+            max_depth: self.max_depth,
+            flattened: false,
+            unknown: self.unknown.as_deref_mut(),
+            tag: None
+        }
+    }
+
+    /// The context of a `#[gon(flatten)]` field, which is converted from the object of its struct.
+    #[doc(hidden)]
+    pub fn flatten(&mut self) -> Context<'_> {
+        Context { flattened: true, ..self.field() }
+    }
+
+    /// If the object belongs to a struct this is flattened into, which checks its keys instead.
+    #[doc(hidden)]
+    pub fn is_flattened(&self) -> bool {
+        self.flattened
+    }
+
+    /// The context of the content of a newtype variant of an internally tagged enum, whose object also contains the
+    /// tag, so the tag isn't an unknown field of the content.
+    #[doc(hidden)]
+    pub fn with_tag(&mut self, tag: &'static str) -> Context<'_> {
+        Context { tag: Some(tag), ..self.field() }
+    }
+
+    /// Reports a key that no field reads, failing if unknown fields are denied.
+    #[doc(hidden)]
+    pub fn unknown_field(&mut self, key: &str, fields: &[&'static str], deny: bool) -> Result<(), FromGonError> {
+        if self.tag == Some(key) || (!deny && self.unknown.is_none()) {
+            return Ok(());
+        }
+        let unknown = UnknownField { key: key.to_owned(), suggestion: suggest(key, fields) };
+        if deny {
+            return Err(FromGonError::UnknownField(unknown));
+        }
+        if let Some(collected) = self.unknown.as_deref_mut() {
+            collected.push(unknown);
+        }
+        Ok(())
+    }
+
+    /// Runs an attempt to convert a variant of an untagged enum, dropping the unknown fields it collected if it
+    /// fails.
+    #[doc(hidden)]
+    pub fn try_variant<T>(
+        &mut self,
+        convert: impl FnOnce(Context<'_>) -> Result<T, FromGonError>
+    ) -> Result<T, FromGonError> {
+        let collected_before = self.unknown.as_ref().map(|collected| collected.len());
+        let (flattened, tag) = (self.flattened, self.tag);
+        let res = convert(Context { flattened, tag, ..self.field() });
+        if let (Err(_), Some(collected), Some(len)) = (&res, self.unknown.as_deref_mut(), collected_before) {
+            collected.truncate(len);
+        }
+        res
+    }
+}
+
+/// A key of an object that isn't read by the derived struct it is converted into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownField {
+    pub key: String,
+    /// The most similar field name if it is similar enough for the key to be a typo
+    pub suggestion: Option<&'static str>
+}
+impl fmt::Display for UnknownField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown field '{}'", self.key)?;
+        if let Some(suggestion) = self.suggestion {
+            write!(f, ", did you mean '{suggestion}'?")?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts `gon`, collecting the keys that derived structs don't read instead of ignoring them:
+/// `let (config, unknown) = collect_unknown_fields::<Config>(&gon);`
+/// Structs with `#[gon(deny_unknown_fields)]` still fail on unknown keys.
+pub fn collect_unknown_fields<T: FromGon>(gon: &Gon) -> (Result<T, FromGonError>, Vec<UnknownField>) {
+    let mut unknown = Vec::new();
+    let res = T::from_gon_with_context(gon, Context::new().collect_unknown_fields(&mut unknown));
+    (res, unknown)
+}
+
+/// Finds the field that is closest to the key, allowing about one typo per three characters.
+fn suggest(key: &str, fields: &[&'static str]) -> Option<&'static str> {
+    let max_distance = (key.chars().count() / 3).max(1);
+    fields.iter()
+        .map(|field| (edit_distance(key, field), *field))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, field)| field)
+}
+
+/// The Levenshtein distance between two strings in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
 pub trait FromGon {
     fn from_gon(gon: &Gon) -> Result<Self, FromGonError> where Self: Sized;
+
+    /// Converts with the context of an outer conversion or with options like `Context::max_depth`. Implemented by the
+    /// derive macros and types containing other values.
+    fn from_gon_with_context(gon: &Gon, _context: Context<'_>) -> Result<Self, FromGonError> where Self: Sized {
+        Self::from_gon(gon)
+    }
+
+    /// Adds the keys read from an object to check the keys of structs this is flattened into. Returns false if any
+    /// key may be read. Implemented by the derive macros.
+    #[doc(hidden)]
+    fn known_keys(_keys: &mut Vec<&'static str>) -> bool where Self: Sized {
+        false
+    }
 }
 
 macro_rules! parse_impls {
@@ -83,7 +267,7 @@ parse_impls!(u8 u16 u32 u64 u128 i8 i16 i32 i64 i128);
 impl FromGon for String {
     fn from_gon(gon: &Gon) -> Result<Self, FromGonError> {
         match gon {
-            Gon::Value(val) => Ok(val.clone()),
+            Gon::Value(val) => Ok(val.text.clone()),
             Gon::Object(_) | Gon::Array(_) => Err(FromGonError::ExpectedValue)
         }
     }
@@ -92,6 +276,10 @@ impl FromGon for String {
 impl<T: FromGon, const N: usize> FromGon for [T; N] {
     fn from_gon(gon: &Gon) -> Result<Self, FromGonError>
     where Self: Sized {
+        Self::from_gon_with_context(gon, Context::new())
+    }
+
+    fn from_gon_with_context(gon: &Gon, mut context: Context<'_>) -> Result<Self, FromGonError> {
         match gon {
             Gon::Object(_) | Gon::Value(_) => Err(FromGonError::ExpectedArray),
             Gon::Array(arr) => {
@@ -101,7 +289,7 @@ impl<T: FromGon, const N: usize> FromGon for [T; N] {
                         found: arr.len()
                     })
                 }
-                let array_vec = arr.into_iter().map(|entry| T::from_gon(entry)).collect::<Result<ArrayVec<T, N>, _>>()?;
+                let array_vec = arr.iter().map(|entry| T::from_gon_with_context(entry, context.field())).collect::<Result<ArrayVec<T, N>, _>>()?;
                 // SAFETY: the length is checked to be equal in the if check above. The map also doesn't filter any values.
                 Ok(unsafe { array_vec.into_inner_unchecked() })
             }
@@ -112,15 +300,41 @@ impl<T: FromGon, const N: usize> FromGon for [T; N] {
 impl<T: FromGon> FromGon for Vec<T> {
     fn from_gon(gon: &Gon) -> Result<Self, FromGonError>
     where Self: Sized {
+        Self::from_gon_with_context(gon, Context::new())
+    }
+
+    fn from_gon_with_context(gon: &Gon, mut context: Context<'_>) -> Result<Self, FromGonError> {
         match gon {
             Gon::Object(_) | Gon::Value(_) => Err(FromGonError::ExpectedArray),
             Gon::Array(arr) => {
-                arr.into_iter().map(|entry| T::from_gon(entry)).collect::<Result<Vec<T>, _>>()
+                arr.iter().map(|entry| T::from_gon_with_context(entry, context.field())).collect::<Result<Vec<T>, _>>()
             }
         }
     }
 }
 
+/// A bare `null` is `None`, anything else is converted to `T`. Derived impls also use `None` for missing fields.
+impl<T: FromGon> FromGon for Option<T> {
+    fn from_gon(gon: &Gon) -> Result<Self, FromGonError>
+    where Self: Sized {
+        match gon {
+            Gon::Value(val) if val.is_null() => Ok(None),
+            _ => T::from_gon(gon).map(Some)
+        }
+    }
+
+    fn from_gon_with_context(gon: &Gon, context: Context<'_>) -> Result<Self, FromGonError> {
+        match gon {
+            Gon::Value(val) if val.is_null() => Ok(None),
+            _ => T::from_gon_with_context(gon, context).map(Some)
+        }
+    }
+
+    fn known_keys(keys: &mut Vec<&'static str>) -> bool {
+        T::known_keys(keys)
+    }
+}
+
 impl FromGon for Gon {
     fn from_gon(gon: &Gon) -> Result<Self, FromGonError>
     where Self: Sized {
@@ -131,10 +345,30 @@ impl FromGon for Gon {
 impl<T: FromGon> FromGon for HashMap<String, T> {
     fn from_gon(gon: &Gon) -> Result<Self, FromGonError>
     where Self: Sized {
+        Self::from_gon_with_context(gon, Context::new())
+    }
+
+    fn from_gon_with_context(gon: &Gon, mut context: Context<'_>) -> Result<Self, FromGonError> {
         match gon {
             Gon::Array(_) | Gon::Value(_) => Err(FromGonError::ExpectedObject),
             Gon::Object(map) => {
-                map.iter().map(|(key, val)| Ok((key.clone(), T::from_gon(val)?))).collect::<Result<HashMap<String, T>, _>>()
+                map.iter().map(|(key, val)| Ok((key.clone(), T::from_gon_with_context(val, context.field())?))).collect::<Result<HashMap<String, T>, _>>()
+            }
+        }
+    }
+}
+
+impl<T: FromGon> FromGon for IndexMap<String, T> {
+    fn from_gon(gon: &Gon) -> Result<Self, FromGonError>
+    where Self: Sized {
+        Self::from_gon_with_context(gon, Context::new())
+    }
+
+    fn from_gon_with_context(gon: &Gon, mut context: Context<'_>) -> Result<Self, FromGonError> {
+        match gon {
+            Gon::Array(_) | Gon::Value(_) => Err(FromGonError::ExpectedObject),
+            Gon::Object(map) => {
+                map.iter().map(|(key, val)| Ok((key.clone(), T::from_gon_with_context(val, context.field())?))).collect::<Result<IndexMap<String, T>, _>>()
             }
         }
     }
@@ -142,7 +376,7 @@ impl<T: FromGon> FromGon for HashMap<String, T> {
 
 #[cfg(test)]
 mod tests {
-    use super::FromGonError;
+    use super::{edit_distance, suggest, FromGonError};
 
 
     #[test]
@@ -164,4 +398,16 @@ mod tests {
         
         Ok(())
     }
+
+    #[test]
+    fn suggestions() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("über", "uber"), 1);
+        let fields = ["whirly_widgets", "location", "id"];
+        assert_eq!(suggest("whirly_widgts", &fields), Some("whirly_widgets"));
+        assert_eq!(suggest("locaton", &fields), Some("location"));
+        assert_eq!(suggest("ib", &fields), Some("id"));
+        assert_eq!(suggest("name", &fields), None);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,210 @@
+//! Writing GON as JSON. Objects and arrays map directly and values are written with their JSON type as classified by
+//! `GonValue`: quoted values and bare text become strings while bare numbers, `true`, `false` and `null` keep their
+//! type.
+
+use std::fmt::Write;
+
+use crate::{diff::{self, PathSegment}, writer::WriteStyle, Gon, GonValue};
+
+/// A bare value that isn't a JSON number, bool or null but resembles one, so it isn't clear whether it was meant as a
+/// string. Returned when writing in strict mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmbiguousValue {
+    /// JSON Pointer to the value
+    pub path: String,
+    pub value: String
+}
+impl std::fmt::Display for AmbiguousValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the JSON type of '{}' at '{}' is ambiguous, quote it to make it a string", self.value, self.path)
+    }
+}
+impl std::error::Error for AmbiguousValue { }
+
+/// Writes the GON as JSON in the provided style. Values that aren't numbers, bools or null are written as strings.
+pub fn write(gon: &Gon, style: WriteStyle) -> String {
+    let mut writer = JsonWriter { out: String::new(), style, depth: 0, strict: false, path: Vec::new() };
+    writer.val(gon).expect("Only strict mode can fail");
+    writer.out
+}
+
+/// Writes the GON as JSON, failing on bare values like `01`, `+1`, `.5`, `0x1F`, `True` or `NaN` that look like
+/// numbers, bools or null but aren't valid JSON.
+pub fn write_strict(gon: &Gon, style: WriteStyle) -> Result<String, AmbiguousValue> {
+    let mut writer = JsonWriter { out: String::new(), style, depth: 0, strict: true, path: Vec::new() };
+    writer.val(gon)?;
+    Ok(writer.out)
+}
+
+impl Gon {
+    /// Writes the GON as compact JSON. See the `json` module for how values are typed.
+    pub fn to_json(&self) -> String {
+        write(self, WriteStyle::Compact)
+    }
+
+    /// Writes the GON as JSON with an indentation of 4 spaces.
+    pub fn to_json_pretty(&self) -> String {
+        write(self, WriteStyle::PRETTY)
+    }
+}
+
+/// Checks if bare text that is classified as a string resembles another JSON type.
+fn is_ambiguous(s: &str) -> bool {
+    let unsigned = s.strip_prefix(['+', '-']).unwrap_or(s);
+    let numeric = unsigned.strip_prefix('.').unwrap_or(unsigned).starts_with(|c: char| c.is_ascii_digit());
+    numeric || matches!(
+        unsigned.to_ascii_lowercase().as_str(),
+        "true" | "false" | "null" | "nan" | "inf" | "infinity"
+    )
+}
+
+struct JsonWriter {
+    out: String,
+    style: WriteStyle,
+    depth: usize,
+    strict: bool,
+    path: Vec<PathSegment>
+}
+impl JsonWriter {
+    fn val(&mut self, gon: &Gon) -> Result<(), AmbiguousValue> {
+        match gon {
+            Gon::Value(val) => self.scalar(val)?,
+            Gon::Object(map) if map.is_empty() => self.out.push_str("{}"),
+            Gon::Object(map) => {
+                self.out.push('{');
+                self.depth += 1;
+                for (i, (key, val)) in map.iter().enumerate() {
+                    if i != 0 {
+                        self.out.push(',');
+                    }
+                    self.line_break();
+                    self.string(key);
+                    self.out.push(':');
+                    if matches!(self.style, WriteStyle::Pretty { .. }) {
+                        self.out.push(' ');
+                    }
+                    self.path.push(PathSegment::Key(key.clone()));
+                    self.val(val)?;
+                    self.path.pop();
+                }
+                self.depth -= 1;
+                self.line_break();
+                self.out.push('}');
+            }
+            Gon::Array(arr) => {
+                let multiline = matches!(self.style, WriteStyle::Pretty { .. })
+                    && arr.iter().any(|val| !matches!(val, Gon::Value(_)));
+                self.out.push('[');
+                self.depth += 1;
+                for (i, val) in arr.iter().enumerate() {
+                    if i != 0 {
+                        self.out.push(',');
+                        if !multiline && matches!(self.style, WriteStyle::Pretty { .. }) {
+                            self.out.push(' ');
+                        }
+                    }
+                    if multiline {
+                        self.line_break();
+                    }
+                    self.path.push(PathSegment::Index(i));
+                    self.val(val)?;
+                    self.path.pop();
+                }
+                self.depth -= 1;
+                if multiline {
+                    self.line_break();
+                }
+                self.out.push(']');
+            }
+        }
+        Ok(())
+    }
+
+    fn scalar(&mut self, val: &GonValue) -> Result<(), AmbiguousValue> {
+        if !val.is_string() {
+            self.out.push_str(val);
+            return Ok(());
+        }
+        if self.strict && !val.quoted && is_ambiguous(val) {
+            return Err(AmbiguousValue { path: diff::pointer(&self.path), value: val.text.clone() });
+        }
+        self.string(val);
+        Ok(())
+    }
+
+    fn string(&mut self, s: &str) {
+        self.out.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => self.out.push_str("\\\""),
+                '\\' => self.out.push_str("\\\\"),
+                '\x08' => self.out.push_str("\\b"),
+                '\x0C' => self.out.push_str("\\f"),
+                '\n' => self.out.push_str("\\n"),
+                '\r' => self.out.push_str("\\r"),
+                '\t' => self.out.push_str("\\t"),
+                c if c < ' ' => write!(self.out, "\\u{:04x}", c as u32).unwrap(),
+                c => self.out.push(c)
+            }
+        }
+        self.out.push('"');
+    }
+
+    /// Starts a new indented line in pretty style.
+    fn line_break(&mut self) {
+        if let WriteStyle::Pretty { indent } = self.style {
+            self.out.push('\n');
+            self.out.extend(std::iter::repeat_n(' ', indent * self.depth));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{writer::WriteStyle, Gon};
+    use super::{write_strict, AmbiguousValue};
+
+    #[test]
+    fn to_json() {
+        let json = r#"{"name":"Big Factory","id":"123","widgets":[8346,-1.5e3,true,null],"open":"false","empty":{},"none":[]}"#;
+        let gon = Gon::parse(json).unwrap();
+        assert_eq!(gon.to_json(), json);
+
+        let gon = Gon::parse(r#"
+            big_factory {
+                location "New York"
+                escapes "tab\t quote\" \u0001"
+                products [{ name A } 2]
+            }
+        "#).unwrap();
+        assert_eq!(gon.to_json(), r#"{"big_factory":{"location":"New York","escapes":"tab\t quote\" \u0001","products":[{"name":"A"},2]}}"#);
+        assert_eq!(gon.to_json_pretty(), "\
+{
+    \"big_factory\": {
+        \"location\": \"New York\",
+        \"escapes\": \"tab\\t quote\\\" \\u0001\",
+        \"products\": [
+            {
+                \"name\": \"A\"
+            },
+            2
+        ]
+    }
+}");
+        assert_eq!(Gon::parse("[1 2 x]").unwrap().to_json_pretty(), r#"[1, 2, "x"]"#);
+        assert_eq!(Gon::parse("word").unwrap().to_json(), r#""word""#);
+    }
+
+    #[test]
+    fn strict() {
+        let gon = Gon::parse(r#"a [1 "01" x] b { c 01 }"#).unwrap();
+        assert_eq!(gon.to_json(), r#"{"a":[1,"01","x"],"b":{"c":"01"}}"#);
+        assert_eq!(write_strict(&gon, WriteStyle::Compact), Err(AmbiguousValue { path: "/b/c".to_owned(), value: "01".to_owned() }));
+        for ambiguous in ["+1", "1.", ".5", "0x1F", "True", "NaN", "-inf", "2nd"] {
+            assert!(write_strict(&Gon::Value(ambiguous.into()), WriteStyle::Compact).is_err(), "{ambiguous}");
+        }
+        for fine in ["-", "x1", "\"01\"", "1e5", "nothing"] {
+            assert!(write_strict(&Gon::parse(fine).unwrap(), WriteStyle::Compact).is_ok(), "{fine}");
+        }
+    }
+}
@@ -1,16 +1,58 @@
-use std::{collections::HashMap, fmt::Debug, ops::Index, str::FromStr, convert::Infallible};
+use std::{fmt::Debug, ops::{Index, IndexMut}, str::FromStr, convert::Infallible};
 
 use from::FromGonError;
-use parser::{Parser, StrParser};
 
 pub mod parser;
 pub mod from;
+pub mod to;
+pub mod writer;
+pub mod borrowed;
+pub mod path;
+pub mod merge;
+pub mod diff;
+pub mod value;
+pub mod json;
+pub mod events;
+#[cfg(feature = "serde")]
+pub mod de;
+#[cfg(feature = "serde")]
+pub mod ser;
 
-pub use gon_derive::FromGon;
+pub use gon_derive::{FromGon, ToGon, FromBorrowedGon};
+pub use indexmap::IndexMap;
+pub use value::GonValue;
+pub use parser::{ParseOptions, Dialect, DuplicateKeys};
+pub use events::{Event, Events, ReaderEvents};
+#[cfg(feature = "serde")]
+pub use de::{from_str, from_gon};
+#[cfg(feature = "serde")]
+pub use ser::{to_string, to_string_pretty, to_gon};
 
 
+/// A location in the parsed source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// Byte offset from the start of the source
+    pub offset: usize,
+    /// Line number, starting at 1
+    pub line: usize,
+    /// Column in characters, starting at 1
+    pub column: usize
+}
+impl Default for Position {
+    fn default() -> Self {
+        Self { offset: 0, line: 1, column: 1 }
+    }
+}
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
 #[derive(Debug)]
-pub enum GonError {
+pub enum GonErrorKind {
+    /// No longer returned by the parser, which reports the specific error instead
     InvalidGon,
     StringExpected,
     EndOfFileExpected,
@@ -23,12 +65,92 @@ pub enum GonError {
     EscapeCharacterExpected,
     InvalidHexEscape,
     InvalidUtf8,
+    /// No longer returned by the parser, which decodes `\uXXXX` escapes
+    #[deprecated(note = "hex escapes are decoded, so this error is never returned")]
     HexEscapesNotSupported,
+    Io(std::io::Error),
+    /// JSON requires a colon after each key
+    ColonExpected,
+    /// JSON requires commas between object entries and array elements
+    CommaExpected,
+    /// JSON doesn't allow a comma after the last entry or element
+    TrailingComma,
+    /// JSON requires keys and strings to be quoted
+    UnquotedString,
+    /// A bare value that isn't a valid JSON number
+    InvalidNumber,
+    /// JSON strings can't contain control characters without escaping them
+    UnescapedControlCharacter(char),
+    /// JSON doesn't have comments
+    CommentsNotAllowed,
+    /// Objects and arrays are nested deeper than the limit of the parse options
+    DepthLimitExceeded(usize),
+    /// A key or value is longer than the limit of the parse options in bytes
+    StringTooLong(usize),
+    /// The source contains more objects, arrays and values than the limit of the parse options
+    TooManyNodes(usize),
     Custom(String)
 }
+impl std::fmt::Display for GonErrorKind {
+    #[allow(deprecated)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidGon => write!(f, "invalid GON"),
+            Self::StringExpected => write!(f, "expected a string"),
+            Self::EndOfFileExpected => write!(f, "expected end of file"),
+            Self::QuoteExpected => write!(f, "unterminated string, expected a closing quote"),
+            Self::ClosingBraceExpected => write!(f, "expected a closing brace '}}'"),
+            Self::ClosingBracketExpected => write!(f, "expected a closing bracket ']'"),
+            Self::ValueExpected => write!(f, "expected a value"),
+            Self::DuplicateKey(key) => write!(f, "duplicate key '{key}'"),
+            Self::UnexpectedEscapeCharacter(c) => write!(f, "unexpected escape character '{}'", c.escape_default()),
+            Self::EscapeCharacterExpected => write!(f, "expected an escape character"),
+            Self::InvalidHexEscape => write!(f, "invalid hex escape"),
+            Self::InvalidUtf8 => write!(f, "invalid UTF-8"),
+            Self::HexEscapesNotSupported => write!(f, "hex escapes are not supported"),
+            Self::Io(err) => write!(f, "I/O error: {err}"),
+            Self::ColonExpected => write!(f, "expected a colon ':'"),
+            Self::CommaExpected => write!(f, "expected a comma ','"),
+            Self::TrailingComma => write!(f, "trailing comma"),
+            Self::UnquotedString => write!(f, "strings have to be quoted"),
+            Self::InvalidNumber => write!(f, "invalid number"),
+            Self::UnescapedControlCharacter(c) => write!(f, "control character '{}' has to be escaped", c.escape_default()),
+            Self::CommentsNotAllowed => write!(f, "comments are not allowed"),
+            Self::DepthLimitExceeded(max) => write!(f, "nesting exceeds the maximum depth of {max}"),
+            Self::StringTooLong(max) => write!(f, "string exceeds the maximum length of {max} bytes"),
+            Self::TooManyNodes(max) => write!(f, "more than the maximum of {max} values"),
+            Self::Custom(msg) => write!(f, "{msg}")
+        }
+    }
+}
+
+/// An error that occured while parsing GON, along with the position it occured at.
+#[derive(Debug)]
+pub struct GonError {
+    pub kind: GonErrorKind,
+    pub pos: Position,
+    /// The source line containing the error, if the parser has access to it.
+    pub snippet: Option<String>
+}
+impl GonError {
+    pub fn new(kind: GonErrorKind, pos: Position) -> Self {
+        Self { kind, pos, snippet: None }
+    }
+}
 impl std::fmt::Display for GonError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        write!(f, "{} at {}", self.kind, self.pos)?;
+        if let Some(snippet) = &self.snippet {
+            let line_number = self.pos.line.to_string();
+            let gutter = " ".repeat(line_number.len());
+            // keep tabs so the caret lines up with the snippet
+            let caret_offset: String = snippet.chars()
+                .take(self.pos.column - 1)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            write!(f, "\n{gutter} |\n{line_number} | {snippet}\n{gutter} | {caret_offset}^")?;
+        }
+        Ok(())
     }
 }
 impl std::error::Error for GonError { }
@@ -43,11 +165,103 @@ pub enum GonGetError<E> {
     ConversionFailed(E)
 }
 
-#[derive(Debug, Clone)]
+/// A GON object, array or value. Trees of any depth are dropped and cloned without recursion, but comparing them,
+/// `Debug`, writing, JSON export, merging and diffing recurse for every level. Parsed trees are limited by
+/// `ParseOptions::max_depth`, trees built in code should stay within a similar depth.
+#[derive(Debug, PartialEq)]
 pub enum Gon {
-    Object(HashMap<String, Gon>),
+    Object(IndexMap<String, Gon>),
     Array(Vec<Gon>),
-    Value(String)
+    Value(GonValue)
+}
+
+/// Drops nested objects and arrays without recursion, so deeply nested trees can't overflow the stack.
+impl Drop for Gon {
+    fn drop(&mut self) {
+        fn take_children(gon: &mut Gon, stack: &mut Vec<Gon>) {
+            match gon {
+                Gon::Object(map) => stack.extend(map.drain(..).map(|(_, val)| val)),
+                Gon::Array(arr) => stack.append(arr),
+                Gon::Value(_) => ()
+            }
+        }
+        let has_nested = match self {
+            Self::Object(map) => map.values().any(|val| !matches!(val, Self::Value(_))),
+            Self::Array(arr) => arr.iter().any(|val| !matches!(val, Self::Value(_))),
+            Self::Value(_) => false
+        };
+        if !has_nested {
+            return;
+        }
+        let mut stack = Vec::new();
+        take_children(self, &mut stack);
+        while let Some(mut gon) = stack.pop() {
+            take_children(&mut gon, &mut stack);
+        }
+    }
+}
+
+/// Clones without recursion, so deeply nested trees can't overflow the stack.
+impl Clone for Gon {
+    fn clone(&self) -> Self {
+        enum Frame<'a> {
+            Object(IndexMap<String, Gon>, indexmap::map::Iter<'a, String, Gon>, Option<&'a String>),
+            Array(Vec<Gon>, std::slice::Iter<'a, Gon>)
+        }
+        let mut stack = Vec::new();
+        let mut next = self;
+        loop {
+            let mut cloned = match next {
+                Self::Value(val) => Some(Self::Value(val.clone())),
+                Self::Object(map) => {
+                    stack.push(Frame::Object(IndexMap::with_capacity(map.len()), map.iter(), None));
+                    None
+                }
+                Self::Array(arr) => {
+                    stack.push(Frame::Array(Vec::with_capacity(arr.len()), arr.iter()));
+                    None
+                }
+            };
+            // add cloned values to their parents until one of them has another child to clone
+            loop {
+                let Some(frame) = stack.last_mut() else {
+                    return cloned.expect("the root is cloned last");
+                };
+                match frame {
+                    Frame::Object(map, iter, key) => {
+                        if let Some(cloned) = cloned.take() {
+                            map.insert(key.take().unwrap().clone(), cloned);
+                        }
+                        if let Some((child_key, child)) = iter.next() {
+                            *key = Some(child_key);
+                            next = child;
+                            break;
+                        }
+                    }
+                    Frame::Array(arr, iter) => {
+                        arr.extend(cloned.take());
+                        if let Some(child) = iter.next() {
+                            next = child;
+                            break;
+                        }
+                    }
+                }
+                cloned = Some(match stack.pop().unwrap() {
+                    Frame::Object(map, _, _) => Self::Object(map),
+                    Frame::Array(arr, _) => Self::Array(arr)
+                });
+            }
+        }
+    }
+}
+
+/// Writes the GON in compact style or in pretty style when using the alternate flag (`{:#}`).
+/// See the `writer` module for more options.
+impl std::fmt::Display for Gon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let style = if f.alternate() { writer::WriteStyle::PRETTY } else { writer::WriteStyle::Compact };
+        writer::write_to(f, self, style)
+    }
 }
 
 impl Index<&str> for Gon {
@@ -72,6 +286,55 @@ impl Index<usize> for Gon {
     }
 }
 
+impl IndexMut<&str> for Gon {
+    fn index_mut(&mut self, index: &str) -> &mut Self::Output {
+        match self {
+            Self::Object(map) => &mut map[index],
+            Self::Array(_) => panic!("Tried to string-index into GON array!"),
+            Self::Value(_) => panic!("Tried to index into GON value!")
+        }
+    }
+}
+
+impl IndexMut<usize> for Gon {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match self {
+            Self::Array(arr) => &mut arr[index],
+            Self::Value(_) => panic!("Tried to int-index into GON value!"),
+            Self::Object(_) => panic!("Tried to int-index into GON object!")
+        }
+    }
+}
+
+/// An entry of a GON object, obtained with `Gon::entry`.
+pub struct Entry<'a>(indexmap::map::Entry<'a, String, Gon>);
+impl<'a> Entry<'a> {
+    pub fn key(&self) -> &str {
+        self.0.key()
+    }
+
+    /// Inserts the default if the entry is vacant and returns a mutable reference to the value.
+    pub fn or_insert(self, default: Gon) -> &'a mut Gon {
+        self.0.or_insert(default)
+    }
+
+    /// Inserts the result of the function if the entry is vacant and returns a mutable reference to the value.
+    pub fn or_insert_with(self, default: impl FnOnce() -> Gon) -> &'a mut Gon {
+        self.0.or_insert_with(default)
+    }
+
+    /// Gets the entry for a key of a nested object. If this entry is vacant, an empty object is inserted first.
+    /// Panics if the present value isn't an object.
+    pub fn entry(self, key: impl Into<String>) -> Entry<'a> {
+        self.or_insert_with(|| Gon::Object(IndexMap::new())).entry(key)
+    }
+
+    /// Like `Entry::entry` but returns an error instead of panicking if the present value isn't an object.
+    pub fn try_entry(self, key: impl Into<String>) -> Result<Entry<'a>, GonGetError<Infallible>> {
+        self.or_insert_with(|| Gon::Object(IndexMap::new())).try_entry(key)
+    }
+}
+
 impl Gon {
     /// Tries to get the GON as a value of a specific type that can be converted from a string.
     /// Will panic on invalid type of object or a conversion fail. Use `try_get`
@@ -156,7 +419,33 @@ impl Gon {
         }
     }
 
+    /// Takes the entries out of an object or returns the GON unchanged if it isn't one. `Gon` implements `Drop` to
+    /// drop deep trees without recursion, so its contents can't be moved out with a `match` or `let` pattern.
+    pub fn into_object(mut self) -> Result<IndexMap<String, Gon>, Gon> {
+        if let Self::Object(map) = &mut self {
+            return Ok(std::mem::take(map));
+        }
+        Err(self)
+    }
+
+    /// Takes the elements out of an array or returns the GON unchanged if it isn't one.
+    pub fn into_array(mut self) -> Result<Vec<Gon>, Gon> {
+        if let Self::Array(arr) = &mut self {
+            return Ok(std::mem::take(arr));
+        }
+        Err(self)
+    }
+
+    /// Takes the value out of the GON or returns it unchanged if it is an object or array.
+    pub fn into_value(mut self) -> Result<GonValue, Gon> {
+        if let Self::Value(val) = &mut self {
+            return Ok(std::mem::take(val));
+        }
+        Err(self)
+    }
+
     /// Returns the size if the GON is an array and panics otherwise.
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         match self {
             Self::Array(arr) => arr.len(),
@@ -165,39 +454,210 @@ impl Gon {
         }
     }
 
-    pub fn parse(s: &str) -> Result<Self, GonError> {
-        let mut p = StrParser::new(s);
-        p.skip_whitespace();
-        
-        // This has some ugly edge cases to make parsing of single values work
-        let gon = match p.peek() {
-            // Check for object/array
-            Some('{') | Some('[') => p.parse_val()?,
-            // try to parse as object otherwise because the outermost braces are optional
-            _ => match p.parse_object() {
-                // if that fails with a 'ValueExpected' error, it might be a single value
-                Err(GonError::ValueExpected) => {
-                    println!("Falling back to parsing value: {}", s);
-                    let mut p = StrParser::new(s);
-                    p.skip_whitespace();
-                    if let Ok(gon) = p.parse_val() {
-                        gon
-                    } else {
-                        // not an object and not a value, maybe improve the error message
-                        return Err(GonError::InvalidGon);
-                    }
-                },
-                res => res?
+    /// Checks if the GON is a value that was written in quotes.
+    pub fn is_quoted(&self) -> bool {
+        matches!(self, Self::Value(val) if val.quoted)
+    }
+
+    /// Checks if the GON is a bare JSON integer. See `GonValue` for how values are classified.
+    pub fn is_int(&self) -> bool {
+        matches!(self, Self::Value(val) if val.is_int())
+    }
+
+    /// Checks if the GON is a bare JSON number with a fraction or exponent.
+    pub fn is_float(&self) -> bool {
+        matches!(self, Self::Value(val) if val.is_float())
+    }
+
+    /// Checks if the GON is a bare `true` or `false`.
+    pub fn is_bool(&self) -> bool {
+        matches!(self, Self::Value(val) if val.is_bool())
+    }
+
+    /// Checks if the GON is a bare `null`.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Value(val) if val.is_null())
+    }
+
+    /// Checks if the GON is a value that is neither a number, a bool nor null.
+    pub fn is_string(&self) -> bool {
+        matches!(self, Self::Value(val) if val.is_string())
+    }
+
+    /// Gets the GON as an object and tries to retrieve a mutable reference to a key. If the key isn't present, None
+    /// is returned.
+    pub fn value_mut(&mut self, key: &str) -> Option<&mut Gon> {
+        match self {
+            Self::Object(map) => map.get_mut(key),
+            Self::Value(_) => panic!("Tried to string-index into GON value!"),
+            Self::Array(_) => panic!("Tried to string-index into GON array!")
+        }
+    }
+
+    /// Tries to get the GON as an object and then tries to retrieve a mutable reference to a key.
+    pub fn try_value_mut(&mut self, key: &str) -> Result<Option<&mut Gon>, GonGetError<Infallible>> {
+        match self {
+            Self::Object(map) => Ok(map.get_mut(key)),
+            Self::Value(_) => Err(GonGetError::UnexpectedValue),
+            Self::Array(_) => Err(GonGetError::UnexpectedArray)
+        }
+    }
+
+    /// Tries to get the GON as an array and tries to mutably index it.
+    pub fn try_index_mut(&mut self, index: usize) -> Result<&mut Gon, GonGetError<Infallible>> {
+        match self {
+            Self::Array(arr) => arr.get_mut(index).ok_or(GonGetError::IndexOutOfBounds(index)),
+            Self::Value(_) => Err(GonGetError::UnexpectedValue),
+            Self::Object(_) => Err(GonGetError::UnexpectedObject)
+        }
+    }
+
+    /// Inserts a key into the GON object and returns the previous value. Panics if the GON isn't an object.
+    /// An existing key keeps its position, new keys are appended.
+    pub fn insert(&mut self, key: impl Into<String>, value: Gon) -> Option<Gon> {
+        match self.try_insert(key, value) {
+            Ok(previous) => previous,
+            Err(_) => panic!("Tried to insert into a GON value or array!")
+        }
+    }
+
+    /// Tries to insert a key into the GON object and returns the previous value.
+    pub fn try_insert(&mut self, key: impl Into<String>, value: Gon) -> Result<Option<Gon>, GonGetError<Infallible>> {
+        match self {
+            Self::Object(map) => Ok(map.insert(key.into(), value)),
+            Self::Value(_) => Err(GonGetError::UnexpectedValue),
+            Self::Array(_) => Err(GonGetError::UnexpectedArray)
+        }
+    }
+
+    /// Removes a key from the GON object, keeping the order of the other keys. Panics if the GON isn't an object.
+    pub fn remove(&mut self, key: &str) -> Option<Gon> {
+        match self.try_remove(key) {
+            Ok(removed) => removed,
+            Err(_) => panic!("Tried to remove a key from a GON value or array!")
+        }
+    }
+
+    /// Tries to remove a key from the GON object, keeping the order of the other keys.
+    pub fn try_remove(&mut self, key: &str) -> Result<Option<Gon>, GonGetError<Infallible>> {
+        match self {
+            Self::Object(map) => Ok(map.shift_remove(key)),
+            Self::Value(_) => Err(GonGetError::UnexpectedValue),
+            Self::Array(_) => Err(GonGetError::UnexpectedArray)
+        }
+    }
+
+    /// Gets the entry for a key of the GON object. Panics if the GON isn't an object.
+    /// Entries of nested objects that are created if missing can be retrieved by chaining `Entry::entry`:
+    /// `gon.entry("window").entry("size").or_insert(Gon::Value("800".into()))`
+    pub fn entry(&mut self, key: impl Into<String>) -> Entry<'_> {
+        match self.try_entry(key) {
+            Ok(entry) => entry,
+            Err(_) => panic!("Tried to get an entry of a GON value or array!")
+        }
+    }
+
+    /// Tries to get the entry for a key of the GON object.
+    pub fn try_entry(&mut self, key: impl Into<String>) -> Result<Entry<'_>, GonGetError<Infallible>> {
+        match self {
+            Self::Object(map) => Ok(Entry(map.entry(key.into()))),
+            Self::Value(_) => Err(GonGetError::UnexpectedValue),
+            Self::Array(_) => Err(GonGetError::UnexpectedArray)
+        }
+    }
+
+    /// Appends a value to the GON array. Panics if the GON isn't an array.
+    pub fn push(&mut self, value: Gon) {
+        if self.try_push(value).is_err() {
+            panic!("Tried to push to a GON value or object!");
+        }
+    }
+
+    /// Tries to append a value to the GON array.
+    pub fn try_push(&mut self, value: Gon) -> Result<(), GonGetError<Infallible>> {
+        match self {
+            Self::Array(arr) => {
+                arr.push(value);
+                Ok(())
             }
-        };
-        p.skip_whitespace();
-    
-        if p.peek().is_some() {
-            Err(GonError::EndOfFileExpected)
-        } else {
-            Ok(gon)
+            Self::Value(_) => Err(GonGetError::UnexpectedValue),
+            Self::Object(_) => Err(GonGetError::UnexpectedObject)
         }
     }
+
+    /// Inserts a value into the GON array at the index, shifting all following elements.
+    /// Panics if the GON isn't an array or the index is greater than the length.
+    pub fn insert_at(&mut self, index: usize, value: Gon) {
+        if let Err(err) = self.try_insert_at(index, value) {
+            panic!("Failed to insert into GON array: {err:?}");
+        }
+    }
+
+    /// Tries to insert a value into the GON array at the index, shifting all following elements.
+    pub fn try_insert_at(&mut self, index: usize, value: Gon) -> Result<(), GonGetError<Infallible>> {
+        match self {
+            Self::Array(arr) if index <= arr.len() => {
+                arr.insert(index, value);
+                Ok(())
+            }
+            Self::Array(_) => Err(GonGetError::IndexOutOfBounds(index)),
+            Self::Value(_) => Err(GonGetError::UnexpectedValue),
+            Self::Object(_) => Err(GonGetError::UnexpectedObject)
+        }
+    }
+
+    /// Removes the value at the index from the GON array, shifting all following elements.
+    /// Panics if the GON isn't an array or the index is out of bounds.
+    pub fn remove_at(&mut self, index: usize) -> Gon {
+        match self.try_remove_at(index) {
+            Ok(removed) => removed,
+            Err(err) => panic!("Failed to remove from GON array: {err:?}")
+        }
+    }
+
+    /// Tries to remove the value at the index from the GON array, shifting all following elements.
+    pub fn try_remove_at(&mut self, index: usize) -> Result<Gon, GonGetError<Infallible>> {
+        match self {
+            Self::Array(arr) if index < arr.len() => Ok(arr.remove(index)),
+            Self::Array(_) => Err(GonGetError::IndexOutOfBounds(index)),
+            Self::Value(_) => Err(GonGetError::UnexpectedValue),
+            Self::Object(_) => Err(GonGetError::UnexpectedObject)
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self, GonError> {
+        parser::parse_str(s)
+    }
+
+    /// Parses with the provided options, for example to only accept strict JSON.
+    pub fn parse_with(s: &str, options: &ParseOptions) -> Result<Self, GonError> {
+        parser::parse_str_with(s, options)
+    }
+
+    /// Parses like `Gon::parse` but doesn't stop at the first syntax error. After an error, parsing resumes at the next
+    /// line or closing bracket, so all mistakes in a source are found in one pass. Returns everything that could be
+    /// parsed along with all errors in source order. The GON is only complete if there are no errors.
+    pub fn parse_recovering(s: &str) -> (Self, Vec<GonError>) {
+        Self::parse_recovering_with(s, &ParseOptions::default())
+    }
+
+    /// Parses like `Gon::parse_recovering` with the provided options. Exceeding the node limit still ends parsing and
+    /// the JSON dialect is not recovered from.
+    pub fn parse_recovering_with(s: &str, options: &ParseOptions) -> (Self, Vec<GonError>) {
+        let (gon, errors) = parser::parse_document_recovering(parser::StrParser::with_options(s, *options));
+        (gon.unwrap_or_else(|| Gon::Object(IndexMap::new())), errors)
+    }
+
+    /// Parses GON from a reader without reading it into a string first. The reader is buffered internally and
+    /// decoded as UTF-8 while parsing. I/O errors are returned as `GonErrorKind::Io`.
+    pub fn from_reader(reader: impl std::io::Read) -> Result<Self, GonError> {
+        parser::parse_document(parser::ReadParser::new(std::io::BufReader::new(reader)))
+    }
+
+    /// Parses from a reader like `Gon::from_reader` with the provided options.
+    pub fn from_reader_with(reader: impl std::io::Read, options: &ParseOptions) -> Result<Self, GonError> {
+        parser::parse_document(parser::ReadParser::with_options(std::io::BufReader::new(reader), *options))
+    }
 }
 
 #[doc = include_str!("../README.md")]
@@ -317,6 +777,113 @@ mod test {
             Gon::parse(r#""\b \f \n \r \t \" \\ \/""#).unwrap().str(),
             "\x08 \x0C \n \r \t \" \\ /"
         );
+        assert_eq!(Gon::parse(r#""\u0041\u00e4\u20AC""#).unwrap().str(), "Aä€");
+        assert_eq!(Gon::parse(r#"["\ud83d\ude00" \uD834\uDD1E]"#).unwrap(), Gon::Array(vec![
+            Gon::Value("😀".into()),
+            Gon::Value("𝄞".into())
+        ]));
+        assert_eq!(Gon::parse(r#"key\tvalue 1"#).unwrap()["key\tvalue"].str(), "1");
+
+        for invalid in [r#""\u12""#, r#""\u12G4""#, r#""\ud83d""#, r#""\ud83d x""#, r#""\ud83d\u0041""#, r#""\ude00""#] {
+            let err = Gon::parse(invalid).unwrap_err();
+            assert!(matches!(err.kind, GonErrorKind::InvalidHexEscape), "{invalid}: {err}");
+            assert_eq!(err.pos.offset, 1);
+        }
+    }
+
+    #[test]
+    fn from_reader() {
+        use std::io::{BufReader, Read};
+
+        let src = "
+            factory { location \"Zürich\" widgets [1 2 3] } # a comment
+            emoji 😀
+        ";
+        // a tiny buffer splits multi-byte characters across reads
+        let gon = parser::parse_document::<Gon>(parser::ReadParser::new(BufReader::with_capacity(1, src.as_bytes()))).unwrap();
+        assert_eq!(gon, Gon::parse(src).unwrap());
+        assert_eq!(Gon::from_reader(src.as_bytes()).unwrap(), gon);
+        assert_eq!(Gon::from_reader("\"single value\"".as_bytes()).unwrap().str(), "single value");
+        assert_eq!(Gon::from_reader("[1 2]".as_bytes()).unwrap().len(), 2);
+        assert_eq!(Gon::from_reader("Hello World".as_bytes()).unwrap()["Hello"].str(), "World");
+        assert_eq!(Gon::from_reader("  ".as_bytes()).unwrap(), Gon::Object(IndexMap::new()));
+
+        let err = Gon::from_reader(&b"a 1\nb \"\xC3\x28\""[..]).unwrap_err();
+        assert!(matches!(err.kind, GonErrorKind::InvalidUtf8));
+        assert_eq!(err.pos, Position { offset: 7, line: 2, column: 4 });
+        assert_eq!(err.snippet.as_deref(), Some("b \""));
+
+        let err = Gon::from_reader(&b"a \xF0\x9F\x98"[..]).unwrap_err();
+        assert!(matches!(err.kind, GonErrorKind::InvalidUtf8));
+
+        struct Failing(usize);
+        impl Read for Failing {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.0 == 0 {
+                    return Err(std::io::Error::other("disk on fire"));
+                }
+                self.0 -= 1;
+                buf[0] = b'a';
+                Ok(1)
+            }
+        }
+        let err = Gon::from_reader(Failing(3)).unwrap_err();
+        assert!(matches!(err.kind, GonErrorKind::Io(ref err) if err.to_string() == "disk on fire"));
+        assert_eq!(err.pos.offset, 3);
+    }
+
+    #[test]
+    fn mutation() {
+        let mut gon = Gon::parse("
+            factory { location Basement widgets [1 2 3] }
+            name Test
+        ").unwrap();
+
+        gon["factory"]["location"] = Gon::Value("New York City".into());
+        gon["factory"]["widgets"][0] = Gon::Value("10".into());
+        *gon.value_mut("name").unwrap() = Gon::Value("Changed".into());
+        assert!(gon.value_mut("missing").is_none());
+        assert!(gon["name"].try_value_mut("x").is_err());
+
+        let widgets = &mut gon["factory"]["widgets"];
+        widgets.push(Gon::Value("4".into()));
+        widgets.insert_at(0, Gon::Value("0".into()));
+        assert_eq!(widgets.remove_at(2).str(), "2");
+        assert!(matches!(widgets.try_insert_at(10, Gon::Value("x".into())), Err(GonGetError::IndexOutOfBounds(10))));
+        assert!(matches!(widgets.try_remove_at(4), Err(GonGetError::IndexOutOfBounds(4))));
+        assert!(matches!(widgets.try_index_mut(4), Err(GonGetError::IndexOutOfBounds(4))));
+        assert!(widgets.try_insert("key", Gon::Value("x".into())).is_err());
+
+        assert!(gon.insert("added", Gon::Value("1".into())).is_none());
+        assert_eq!(gon.insert("name", Gon::Value("Replaced".into())).unwrap().str(), "Changed");
+        assert_eq!(gon.remove("added").unwrap().str(), "1");
+        assert!(gon.remove("added").is_none());
+        assert!(gon["name"].try_push(Gon::Array(vec![])).is_err());
+
+        gon.entry("window").entry("size").entry("width").or_insert(Gon::Value("800".into()));
+        *gon.entry("window").entry("size").entry("width").or_insert(Gon::Value("0".into())) = Gon::Value("1024".into());
+        gon.entry("window").entry("title").or_insert_with(|| Gon::Value("GON".into()));
+        assert!(gon.entry("name").try_entry("nested").is_err());
+        assert!(gon["name"].try_entry("nested").is_err());
+
+        assert_eq!(gon, Gon::parse("
+            factory { location \"New York City\" widgets [0 10 3 4] }
+            name Replaced
+            window { size { width 1024 } title GON }
+        ").unwrap());
+        assert_eq!(gon.to_string(), r#"factory {location "New York City" widgets [0 10 3 4]} name Replaced window {size {width 1024} title GON}"#);
+    }
+
+    #[test]
+    fn key_order() {
+        let gon = Gon::parse("
+            zebra 1
+            apple 2
+            mango { z 1 a 2 m 3 }
+        ").unwrap();
+        let Gon::Object(map) = &gon else { panic!("Expected object") };
+        assert_eq!(map.keys().collect::<Vec<_>>(), ["zebra", "apple", "mango"]);
+        assert_eq!(gon.to_string(), "zebra 1 apple 2 mango {z 1 a 2 m 3}");
     }
 
     #[test]
@@ -351,4 +918,206 @@ mod test {
         assert_eq!(gon["text"].str(), " #hashes inside quoted strings aren't comments");
         assert_eq!(gon["text2"].str(), "Hashes_#inside_or_next_to_unquoted_strings_aren't_comments#");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn error_positions() {
+        let err = Gon::parse("a 1\nb {\n  c \"unterminated\n}").unwrap_err();
+        assert!(matches!(err.kind, GonErrorKind::QuoteExpected));
+        assert_eq!(err.pos, Position { offset: 12, line: 3, column: 5 });
+        assert_eq!(err.snippet.as_deref(), Some("  c \"unterminated"));
+
+        let err = Gon::parse("a 1\nb 2\na 3").unwrap_err();
+        assert!(matches!(err.kind, GonErrorKind::DuplicateKey(ref key) if key == "a"));
+        assert_eq!((err.pos.line, err.pos.column), (3, 1));
+
+        let err = Gon::parse("list [1 2 3").unwrap_err();
+        assert!(matches!(err.kind, GonErrorKind::ClosingBracketExpected));
+        assert_eq!(err.pos.offset, 11);
+
+        let err = Gon::parse("täst \"\\q\"").unwrap_err();
+        assert!(matches!(err.kind, GonErrorKind::UnexpectedEscapeCharacter('q')));
+        assert_eq!(err.pos, Position { offset: 7, line: 1, column: 7 });
+        assert_eq!(
+            err.to_string(),
+            "unexpected escape character 'q' at line 1, column 7\n  |\n1 | täst \"\\q\"\n  |       ^"
+        );
+    }
+
+    #[test]
+    fn top_level() {
+        assert_eq!(Gon::parse("  value  ").unwrap(), Gon::Value("value".into()));
+        assert_eq!(Gon::parse("\"a value\" # comment").unwrap().str(), "a value");
+        assert_eq!(Gon::parse("key value").unwrap(), Gon::parse("{ key value }").unwrap());
+        assert_eq!(Gon::parse("# only a comment").unwrap(), Gon::Object(IndexMap::new()));
+
+        let error = |s: &str| {
+            let err = Gon::parse(s).unwrap_err();
+            (err.kind.to_string(), err.pos.offset)
+        };
+        assert_eq!(error("a 1 b"), ("expected a value".to_owned(), 5));
+        assert_eq!(error("a 1 b }"), ("expected a value".to_owned(), 6));
+        assert_eq!(error("}"), ("expected a string".to_owned(), 0));
+        assert_eq!(error("{ a 1 ] }"), ("expected a string".to_owned(), 6));
+        assert_eq!(error("[1 }"), ("expected a value".to_owned(), 3));
+        assert_eq!(error("value ]"), ("expected a value".to_owned(), 6));
+    }
+
+    #[test]
+    fn recovering() {
+        let src = r#"
+            name "Big Factory"
+            location "bad \q escape" ignored
+            widgets [1 2 } 3]
+            name again
+            nested {
+                a 1
+                b ]
+                c 3
+            }
+            last ok
+            open {"#;
+        let (gon, errors) = Gon::parse_recovering(src);
+        assert_eq!(gon.to_string(), r#"name "Big Factory" widgets [1 2] nested {a 1 c 3} last ok open {}"#);
+        let errors: Vec<_> = errors.iter().map(|err| (err.kind.to_string(), err.pos.line, err.pos.column)).collect();
+        assert_eq!(errors, [
+            ("unexpected escape character 'q'".to_owned(), 3, 27),
+            ("expected a value".to_owned(), 4, 26),
+            ("duplicate key 'name'".to_owned(), 5, 13),
+            ("expected a value".to_owned(), 8, 19),
+            ("expected a closing brace '}'".to_owned(), 12, 19)
+        ]);
+
+        let errors = |s: &str| {
+            let (gon, errors) = Gon::parse_recovering(s);
+            (gon.to_string(), errors.iter().map(|err| err.pos.offset).collect::<Vec<_>>())
+        };
+        assert_eq!(errors("a 1 } b 2 } c 3"), ("a 1 b 2 c 3".to_owned(), vec![4, 10]));
+        assert_eq!(errors("} a 1"), ("a 1".to_owned(), vec![0]));
+        assert_eq!(errors("{ a 1 } b"), ("a 1".to_owned(), vec![8]));
+        assert_eq!(errors("[1 [2 3"), ("[1 [2 3]]".to_owned(), vec![7]));
+        assert_eq!(errors("a \"b\"\nc"), (r#"a "b""#.to_owned(), vec![7]));
+        let (gon, errors) = Gon::parse_recovering("a 1 b [2]");
+        assert_eq!(gon, Gon::parse("a 1 b [2]").unwrap());
+        assert!(errors.is_empty());
+
+        let limited = ParseOptions::new().max_nodes(3);
+        let (gon, errors) = Gon::parse_recovering_with("a } b [1 2 3]", &limited);
+        assert_eq!(gon, Gon::Object(IndexMap::new()));
+        assert!(matches!(errors[..], [
+            GonError { kind: GonErrorKind::ValueExpected, .. },
+            GonError { kind: GonErrorKind::TooManyNodes(3), .. }
+        ]));
+    }
+
+    #[test]
+    fn parse_options() {
+        let src = "a 1 b 2 a 3 c { a 1 a [2] a 3 }";
+        let parse = |options: ParseOptions| Gon::parse_with(src, &options);
+        assert!(matches!(parse(ParseOptions::new()).unwrap_err().kind, GonErrorKind::DuplicateKey(ref key) if key == "a"));
+        assert_eq!(parse(ParseOptions::new().duplicate_keys(DuplicateKeys::FirstWins)).unwrap(), Gon::parse("a 1 b 2 c { a 1 }").unwrap());
+        let last = parse(ParseOptions::new().duplicate_keys(DuplicateKeys::LastWins)).unwrap();
+        assert_eq!(last.to_string(), "a 3 b 2 c {a 3}");
+        let collected = parse(ParseOptions::new().duplicate_keys(DuplicateKeys::Collect)).unwrap();
+        assert_eq!(collected.to_string(), "a [1 3] b 2 c {a [1 [2] 3]}");
+        let json = ParseOptions::json().duplicate_keys(DuplicateKeys::Collect);
+        assert_eq!(Gon::parse_with(r#"{"a": 1, "a": 2}"#, &json).unwrap(), Gon::parse("a [1 2]").unwrap());
+
+        let err = Gon::parse_with("a [[1]] b [[[2]]]", &ParseOptions::new().max_depth(3)).unwrap_err();
+        assert!(matches!(err.kind, GonErrorKind::DepthLimitExceeded(3)));
+        assert_eq!(err.pos.offset, 12);
+        assert!(Gon::parse_with("[[[2]]]", &ParseOptions::new().max_depth(3)).is_ok());
+        assert!(Gon::parse_with("[[[[2]]]]", &ParseOptions::json().max_depth(3)).is_err());
+
+        let err = Gon::parse_with(r#"short "a\"b" long abcdef"#, &ParseOptions::new().max_string_length(5)).unwrap_err();
+        assert!(matches!(err.kind, GonErrorKind::StringTooLong(5)));
+        assert_eq!(err.pos.offset, 18);
+        assert!(Gon::parse_with(r#"short "\u0041\u0042\u0043\u0044\u0045""#, &ParseOptions::new().max_string_length(5)).is_ok());
+
+        // the top-level object and its four values
+        assert!(Gon::parse_with("a 1 b [2 3]", &ParseOptions::new().max_nodes(5)).is_ok());
+        let err = Gon::parse_with("a 1 b [2 3 4]", &ParseOptions::new().max_nodes(5)).unwrap_err();
+        assert!(matches!(err.kind, GonErrorKind::TooManyNodes(5)));
+
+        let braces = ParseOptions::new().optional_top_level_braces(false);
+        assert!(Gon::parse_with("{ a 1 }", &braces).is_ok());
+        assert!(Gon::parse_with("value", &braces).is_ok());
+        assert!(matches!(Gon::parse_with("a 1", &braces).unwrap_err().kind, GonErrorKind::EndOfFileExpected));
+        assert!(Gon::parse_with("", &braces).is_err());
+
+        let from_reader = Gon::from_reader_with("a 1 a 2".as_bytes(), &ParseOptions::new().duplicate_keys(DuplicateKeys::LastWins));
+        assert_eq!(from_reader.unwrap(), Gon::parse("a 2").unwrap());
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let hostile = "[".repeat(100_000);
+        for result in [Gon::parse(&hostile), Gon::parse_with(&hostile, &ParseOptions::json()), Gon::from_reader(hostile.as_bytes())] {
+            let err = result.unwrap_err();
+            assert!(matches!(err.kind, GonErrorKind::DepthLimitExceeded(ParseOptions::DEFAULT_MAX_DEPTH)));
+            assert_eq!(err.pos.offset, ParseOptions::DEFAULT_MAX_DEPTH);
+        }
+        let nested = format!("{}{}", "[".repeat(128), "]".repeat(128));
+        assert!(Gon::parse(&nested).is_ok());
+        // an object without braces is a level as well
+        assert!(Gon::parse(&format!("a {}{}", "[".repeat(127), "]".repeat(127))).is_ok());
+        assert!(Gon::parse(&format!("a {}{}", "[".repeat(128), "]".repeat(128))).is_err());
+
+        // trees built without the parser can be nested arbitrarily deep and are still cloned and dropped
+        let mut deep = Gon::Value("leaf".into());
+        for i in 0..100_000 {
+            deep = if i % 2 == 0 { Gon::Array(vec![deep]) } else { Gon::Object(IndexMap::from([("a".to_owned(), deep)])) };
+        }
+        let cloned = deep.clone();
+        let mut inner = &cloned;
+        for _ in 0..100_000 {
+            inner = match inner {
+                Gon::Object(map) => &map["a"],
+                Gon::Array(arr) => &arr[0],
+                Gon::Value(_) => panic!("Expected nested GON")
+            };
+        }
+        assert_eq!(inner.str(), "leaf");
+        drop(deep);
+        drop(cloned);
+    }
+
+    #[test]
+    fn into_contents() {
+        let gon = Gon::parse("a [1 2] b x").unwrap();
+        let mut map = gon.into_object().unwrap();
+        let arr = map.shift_remove("a").unwrap().into_array().unwrap();
+        assert_eq!(arr.len(), 2);
+        assert_eq!(map.shift_remove("b").unwrap().into_value().unwrap().text, "x");
+        let err = Gon::Value("y".into()).into_array().unwrap_err();
+        assert!(err.into_object().is_err());
+    }
+
+    #[test]
+    fn strict_json() {
+        let json = ParseOptions::json();
+        let valid = r#" {"a": [1, -2.5e3, true, null, "s\u00e4"], "b": {}, "c": []} "#;
+        assert_eq!(Gon::parse_with(valid, &json).unwrap(), Gon::parse(valid).unwrap());
+        assert_eq!(Gon::parse_with("\"single\"", &json).unwrap(), Gon::Value(GonValue { text: "single".to_owned(), quoted: true }));
+        assert!(Gon::parse_with("12", &json).unwrap().is_int());
+
+        let error = |s: &str| {
+            let err = Gon::parse_with(s, &json).unwrap_err();
+            (err.kind.to_string(), err.pos.offset)
+        };
+        assert_eq!(error(r#"{"a": 1 "b": 2}"#), ("expected a comma ','".to_owned(), 8));
+        assert_eq!(error(r#"{"a" 1}"#), ("expected a colon ':'".to_owned(), 5));
+        assert_eq!(error(r#"{a: 1}"#), ("strings have to be quoted".to_owned(), 1));
+        assert_eq!(error(r#"["a", b]"#), ("strings have to be quoted".to_owned(), 6));
+        assert_eq!(error(r#"[1, 2,]"#), ("trailing comma".to_owned(), 6));
+        assert_eq!(error(r#"{"a": 1,}"#), ("trailing comma".to_owned(), 8));
+        assert_eq!(error(r#"[01, 1]"#), ("invalid number".to_owned(), 1));
+        assert_eq!(error(r#"[+1]"#), ("invalid number".to_owned(), 1));
+        assert_eq!(error("[1 # comment\n]"), ("comments are not allowed".to_owned(), 3));
+        assert_eq!(error("# comment\n1"), ("comments are not allowed".to_owned(), 0));
+        assert_eq!(error("1 2"), ("expected end of file".to_owned(), 2));
+        assert_eq!(error("a 1"), ("strings have to be quoted".to_owned(), 0));
+        assert_eq!(error(""), ("expected a value".to_owned(), 0));
+        assert_eq!(error("[1"), ("expected a closing bracket ']'".to_owned(), 2));
+        assert_eq!(error("\"tab\tin string\""), ("control character '\\t' has to be escaped".to_owned(), 4));
+    }
+}
@@ -0,0 +1,179 @@
+//! Layering GON on top of other GON, for example to apply a mod or override file onto shipped defaults.
+//!
+//! `Gon::merge` deeply merges objects. `Gon::patch` additionally understands directive keys in the patch:
+//! ```text
+//! $delete [old_key other_key]    # removes keys, also accepts a single key
+//! $replace { stats { hp 10 } }   # sets keys without merging into the existing values
+//! $append { drops [gem] }        # appends to arrays instead of replacing them
+//! ```
+
+use crate::{Gon, IndexMap};
+
+/// What happens when an array is merged onto an array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayMerge {
+    /// The array of the merged GON replaces the existing array.
+    #[default]
+    Replace,
+    /// The elements of the merged GON are appended to the existing array.
+    Append
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// A key starting with `$` that isn't a known directive.
+    UnknownDirective(String),
+    /// `$delete` expects a key or an array of keys.
+    InvalidDelete,
+    /// The directive expects an object of entries.
+    ExpectedObject(&'static str),
+    /// `$append` was used on a key that holds something other than an array.
+    AppendToNonArray(String)
+}
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+impl std::error::Error for PatchError { }
+
+impl Gon {
+    /// Deeply merges another GON into this one. Keys of objects are merged recursively and everything else is
+    /// replaced, including arrays. See `merge_with` for appending arrays.
+    pub fn merge(&mut self, other: Gon) {
+        self.merge_with(other, ArrayMerge::Replace);
+    }
+
+    /// Deeply merges another GON into this one, merging arrays according to the policy.
+    pub fn merge_with(&mut self, mut other: Gon, arrays: ArrayMerge) {
+        match (self, &mut other) {
+            (Gon::Object(map), Gon::Object(other)) => {
+                for (key, val) in std::mem::take(other) {
+                    match map.get_mut(&key) {
+                        Some(existing) => existing.merge_with(val, arrays),
+                        None => {
+                            map.insert(key, val);
+                        }
+                    }
+                }
+            }
+            (Gon::Array(arr), Gon::Array(other)) if arrays == ArrayMerge::Append => arr.append(other),
+            (this, _) => *this = other
+        }
+    }
+
+    /// Applies a patch, which is merged like `merge` but can contain the `$delete`, `$replace` and `$append`
+    /// directives described in the `merge` module.
+    pub fn patch(&mut self, mut patch: Gon) -> Result<(), PatchError> {
+        let Gon::Object(patch_entries) = &mut patch else {
+            *self = patch;
+            return Ok(());
+        };
+        let patch_entries = std::mem::take(patch_entries);
+        if !matches!(self, Gon::Object(_)) {
+            *self = Gon::Object(IndexMap::new());
+        }
+        let Gon::Object(map) = self else { unreachable!() };
+        for (key, mut val) in patch_entries {
+            match key.as_str() {
+                "$delete" => match &val {
+                    Gon::Value(key) => {
+                        map.shift_remove(key.as_str());
+                    }
+                    Gon::Array(keys) => for key in keys {
+                        let Gon::Value(key) = key else { return Err(PatchError::InvalidDelete) };
+                        map.shift_remove(key.as_str());
+                    }
+                    Gon::Object(_) => return Err(PatchError::InvalidDelete)
+                }
+                "$replace" => {
+                    let Gon::Object(entries) = &mut val else { return Err(PatchError::ExpectedObject("$replace")) };
+                    map.extend(std::mem::take(entries));
+                }
+                "$append" => {
+                    let Gon::Object(entries) = &mut val else { return Err(PatchError::ExpectedObject("$append")) };
+                    for (key, mut val) in std::mem::take(entries) {
+                        match (map.get_mut(&key), &mut val) {
+                            (Some(Gon::Array(arr)), Gon::Array(appended)) => arr.append(appended),
+                            (Some(Gon::Array(arr)), _) => arr.push(val),
+                            (Some(_), _) => return Err(PatchError::AppendToNonArray(key)),
+                            (None, Gon::Array(_)) => {
+                                map.insert(key, val);
+                            }
+                            (None, _) => {
+                                map.insert(key, Gon::Array(vec![val]));
+                            }
+                        }
+                    }
+                }
+                directive if directive.starts_with('$') => return Err(PatchError::UnknownDirective(key)),
+                _ => match map.get_mut(&key) {
+                    Some(existing) => existing.patch(val)?,
+                    None => {
+                        // directives in new objects still have to be applied
+                        let mut new = Gon::Object(IndexMap::new());
+                        new.patch(val)?;
+                        map.insert(key, new);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Gon;
+    use super::{ArrayMerge, PatchError};
+
+    const BASE: &str = "
+        player { hp 100 speed 5 items [sword shield] }
+        enemies [slime bat]
+        title Game
+    ";
+
+    #[test]
+    fn merge() {
+        let mut gon = Gon::parse(BASE).unwrap();
+        gon.merge(Gon::parse("player { hp 150 mana 20 items [bow] } title { main Game }").unwrap());
+        assert_eq!(gon, Gon::parse("
+            player { hp 150 speed 5 items [bow] mana 20 }
+            enemies [slime bat]
+            title { main Game }
+        ").unwrap());
+
+        let mut gon = Gon::parse(BASE).unwrap();
+        gon.merge_with(Gon::parse("player { items [bow] } enemies [ghost]").unwrap(), ArrayMerge::Append);
+        assert_eq!(gon["player"]["items"], Gon::parse("[sword shield bow]").unwrap());
+        assert_eq!(gon["enemies"], Gon::parse("[slime bat ghost]").unwrap());
+    }
+
+    #[test]
+    fn patch() {
+        let mut gon = Gon::parse(BASE).unwrap();
+        gon.patch(Gon::parse("
+            player {
+                $delete speed
+                $append { items [bow arrows] potions potion }
+                hp 120
+            }
+            $replace { title { main Modded } }
+            $delete [enemies missing]
+            new { $append { list [1 2] } }
+        ").unwrap()).unwrap();
+        assert_eq!(gon, Gon::parse("
+            player { hp 120 items [sword shield bow arrows] potions [potion] }
+            title { main Modded }
+            new { list [1 2] }
+        ").unwrap());
+        // keys keep their order
+        assert_eq!(gon.to_string(), "player {hp 120 items [sword shield bow arrows] potions [potion]} title {main Modded} new {list [1 2]}");
+
+        let mut gon = Gon::parse(BASE).unwrap();
+        assert_eq!(gon.patch(Gon::parse("$remove x").unwrap()), Err(PatchError::UnknownDirective("$remove".to_owned())));
+        assert_eq!(gon.patch(Gon::parse("$append { title x }").unwrap()), Err(PatchError::AppendToNonArray("title".to_owned())));
+        assert_eq!(gon.patch(Gon::parse("$delete { a b }").unwrap()), Err(PatchError::InvalidDelete));
+        assert_eq!(gon.patch(Gon::parse("$replace x").unwrap()), Err(PatchError::ExpectedObject("$replace")));
+    }
+}
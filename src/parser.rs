@@ -1,100 +1,435 @@
-use std::{collections::HashMap, iter::Peekable, str::Chars};
+use std::{borrow::Cow, hash::Hash, io::{self, BufRead}, ops::Range};
 
-use crate::{Gon, GonError};
+use indexmap::IndexMap;
+
+use crate::{events::{Event, EventParser}, Gon, GonError, GonErrorKind, GonValue, Position};
 
 fn is_whitespace(c: char) -> bool {
     matches!(c, ' ' | '\t' | '\n' | '\r')
 }
 
-pub(crate) trait Parser {
+/// A GON tree the parser can build. Strings are passed as borrowed from the source if possible, so trees can decide
+/// whether they keep borrowing or copy them.
+pub(crate) trait Node<'s>: Sized {
+    type Str: Hash + Eq + AsRef<str>;
+    fn string(s: Cow<'s, str>) -> Self::Str;
+    fn object(map: IndexMap<Self::Str, Self>) -> Self;
+    fn array(arr: Vec<Self>) -> Self;
+    fn value(val: Self::Str, quoted: bool) -> Self;
+}
+
+impl<'s> Node<'s> for Gon {
+    type Str = String;
+    fn string(s: Cow<'s, str>) -> String {
+        s.into_owned()
+    }
+    fn object(map: IndexMap<String, Gon>) -> Self {
+        Gon::Object(map)
+    }
+    fn array(arr: Vec<Gon>) -> Self {
+        Gon::Array(arr)
+    }
+    fn value(val: String, quoted: bool) -> Self {
+        Gon::Value(GonValue { text: val, quoted })
+    }
+}
+
+/// Options controlling how GON is parsed. The fields can be set with the builder methods of the same name:
+/// `ParseOptions::new().duplicate_keys(DuplicateKeys::LastWins).max_depth(64)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub dialect: Dialect,
+    pub duplicate_keys: DuplicateKeys,
+    /// The maximum nesting of objects and arrays, `DEFAULT_MAX_DEPTH` by default. Without a limit, hostile input can
+    /// overflow the stack, so it should only be disabled for trusted sources. Derived conversions have their own limit
+    /// with the same default, see `from::Context::max_depth`.
+    pub max_depth: Option<usize>,
+    /// The maximum length of keys and values in bytes, after escape sequences are decoded
+    pub max_string_length: Option<usize>,
+    /// The maximum number of objects, arrays and values in the source
+    pub max_nodes: Option<usize>,
+    /// Whether the braces of a top-level object can be omitted. If not, the source has to be a single value.
+    /// Only used by the GON dialect.
+    pub optional_top_level_braces: bool
+}
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            dialect: Dialect::Gon,
+            duplicate_keys: DuplicateKeys::Error,
+            max_depth: Some(Self::DEFAULT_MAX_DEPTH),
+            max_string_length: None,
+            max_nodes: None,
+            optional_top_level_braces: true
+        }
+    }
+}
+impl ParseOptions {
+    pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+    /// The default options, which are the same `Gon::parse` uses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Options only accepting strict JSON.
+    pub fn json() -> Self {
+        Self::new().dialect(Dialect::Json)
+    }
+
+    pub fn dialect(self, dialect: Dialect) -> Self {
+        Self { dialect, ..self }
+    }
+
+    pub fn duplicate_keys(self, duplicate_keys: DuplicateKeys) -> Self {
+        Self { duplicate_keys, ..self }
+    }
+
+    pub fn max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth: Some(max_depth), ..self }
+    }
+
+    pub fn max_string_length(self, max_string_length: usize) -> Self {
+        Self { max_string_length: Some(max_string_length), ..self }
+    }
+
+    pub fn max_nodes(self, max_nodes: usize) -> Self {
+        Self { max_nodes: Some(max_nodes), ..self }
+    }
+
+    pub fn optional_top_level_braces(self, optional_top_level_braces: bool) -> Self {
+        Self { optional_top_level_braces, ..self }
+    }
+}
+
+/// What happens when an object contains a key more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeys {
+    /// Fail with `GonErrorKind::DuplicateKey`
+    #[default]
+    Error,
+    /// Keep the first value and ignore the others
+    FirstWins,
+    /// Keep the last value, at the position of the first key
+    LastWins,
+    /// Collect all values of the key into an array, in order
+    Collect
+}
+
+/// The syntax accepted by the parser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    /// GON with optional quotes, commas, colons and top-level braces and `#` comments. This includes JSON.
+    #[default]
+    Gon,
+    /// JSON as specified by RFC 8259: quoted strings, required commas and colons, valid numbers, no comments and a
+    /// single top-level value.
+    Json
+}
+
+/// Parses a whole GON source. The outermost braces of an object are optional and a single value is also valid.
+pub(crate) fn parse_str<'s, N: Node<'s>>(s: &'s str) -> Result<N, GonError> {
+    parse_document(StrParser::new(s))
+}
+
+/// Parses a source with the provided options.
+pub(crate) fn parse_str_with<'s, N: Node<'s>>(s: &'s str, options: &ParseOptions) -> Result<N, GonError> {
+    parse_document(StrParser::with_options(s, *options))
+}
+
+/// Parses a whole GON document in a single pass, so sources that can't be rewound like readers are supported. The tree
+/// is built from the events of the source without recursion.
+pub(crate) fn parse_document<'s, N: Node<'s>>(parser: impl Parser<'s>) -> Result<N, GonError> {
+    build(&mut EventParser::new(parser))
+}
+
+/// Parses a whole GON document, recovering from syntax errors instead of stopping at the first one. Returns the
+/// partial document, if anything could be parsed, and all errors in the order they were found.
+pub(crate) fn parse_document_recovering<'s, N: Node<'s>>(parser: impl Parser<'s>) -> (Option<N>, Vec<GonError>) {
+    let mut events = EventParser::new(parser);
+    events.parser.state().errors = Some(Vec::new());
+    let res = build(&mut events);
+    let mut errors = events.parser.state().errors.take().unwrap_or_default();
+    match res {
+        Ok(gon) => (Some(gon), errors),
+        Err(err) => {
+            errors.push(err);
+            (None, errors)
+        }
+    }
+}
+
+/// An object or array of the tree that is being built.
+enum Frame<'s, N: Node<'s>> {
+    /// The entries so far, values of collected duplicate keys and the key of the value that is being parsed
+    Object(IndexMap<N::Str, N>, Vec<(usize, N)>, Option<(N::Str, Position)>),
+    Array(Vec<N>)
+}
+
+fn build<'s, N: Node<'s>, P: Parser<'s>>(events: &mut EventParser<'s, P>) -> Result<N, GonError> {
+    let mut stack: Vec<Frame<'s, N>> = Vec::new();
+    let mut root = None;
+    while let Some(event) = events.next_event()? {
+        let node = match event {
+            Event::StartObject => {
+                stack.push(Frame::Object(IndexMap::new(), Vec::new(), None));
+                continue;
+            }
+            Event::StartArray => {
+                stack.push(Frame::Array(Vec::new()));
+                continue;
+            }
+            Event::Key(key) => {
+                // in recovering mode, a key whose value failed to parse is replaced by the next one
+                if let Some(Frame::Object(_, _, pending)) = stack.last_mut() {
+                    *pending = Some((N::string(key), events.pos()));
+                }
+                continue;
+            }
+            Event::Value(val) => N::value(N::string(val.text), val.quoted),
+            Event::EndObject => match stack.pop() {
+                Some(Frame::Object(mut map, collected, _)) => {
+                    collect_duplicates(&mut map, collected);
+                    N::object(map)
+                }
+                _ => unreachable!("Objects end after they started")
+            },
+            Event::EndArray => match stack.pop() {
+                Some(Frame::Array(arr)) => N::array(arr),
+                _ => unreachable!("Arrays end after they started")
+            }
+        };
+        match stack.last_mut() {
+            None => root = Some(node),
+            Some(Frame::Array(arr)) => arr.push(node),
+            Some(Frame::Object(map, collected, pending)) => {
+                let (key, key_pos) = pending.take().expect("Values in objects follow their key");
+                if let Err(err) = events.parser.insert_entry(map, collected, key, key_pos, node) {
+                    events.parser.record(err)?;
+                }
+            }
+        }
+    }
+    Ok(root.expect("Events only end after a whole document"))
+}
+
+/// Turns the values of keys that appeared more than once into arrays of all their values, in order.
+fn collect_duplicates<'s, N: Node<'s>>(map: &mut IndexMap<N::Str, N>, collected: Vec<(usize, N)>) {
+    let mut arrays: IndexMap<usize, Vec<N>> = IndexMap::new();
+    for (index, val) in collected {
+        arrays.entry(index).or_default().push(val);
+    }
+    for (index, rest) in arrays {
+        let first = map.get_index_mut(index).unwrap().1;
+        let mut all = vec![std::mem::replace(first, N::array(Vec::new()))];
+        all.extend(rest);
+        *first = N::array(all);
+    }
+}
+
+/// The mutable state of a parse: counters for the limits of the parse options and, in recovering mode, the errors
+/// that were recovered from.
+#[derive(Default)]
+pub(crate) struct ParseState {
+    depth: usize,
+    nodes: usize,
+    errors: Option<Vec<GonError>>
+}
+
+pub(crate) trait Parser<'s> {
     fn next(&mut self) -> Option<char>;
     fn peek(&mut self) -> Option<char>;
-    fn parse_object(&mut self) -> Result<Gon, GonError> {
-        let mut map = HashMap::new();
-        while !matches!(self.peek(), Some('}') | None) {
-            let key = self.parse_string()?;
-            self.skip_whitespace_and_token(':');
-            let val = self.parse_val()?;
-            if map.get(&key).is_some() {
-                return Err(GonError::DuplicateKey(key));
+    /// The position of the next character.
+    fn pos(&self) -> Position;
+    /// Returns the source line containing the position if it is available.
+    fn line_at(&self, _pos: Position) -> Option<String> {
+        None
+    }
+    /// Returns a slice of the source by byte offsets if the parser can borrow from its source.
+    fn slice(&self, _range: Range<usize>) -> Option<&'s str> {
+        None
+    }
+    /// Takes an error of the underlying source. Sources that fail end the input early.
+    fn take_error(&mut self) -> Option<GonError> {
+        None
+    }
+    fn options(&self) -> &ParseOptions;
+    fn state(&mut self) -> &mut ParseState;
+
+    fn error_at(&self, kind: GonErrorKind, pos: Position) -> GonError {
+        GonError { kind, pos, snippet: self.line_at(pos) }
+    }
+
+    fn error(&self, kind: GonErrorKind) -> GonError {
+        self.error_at(kind, self.pos())
+    }
+
+    /// Enters an object or array at the current position, checking the depth limit.
+    fn enter(&mut self) -> Result<(), GonError> {
+        let depth = self.state().depth + 1;
+        match self.options().max_depth {
+            Some(max) if depth > max => Err(self.error(GonErrorKind::DepthLimitExceeded(max))),
+            _ => {
+                self.state().depth = depth;
+                Ok(())
             }
+        }
+    }
+
+    fn leave(&mut self) {
+        self.state().depth -= 1;
+    }
+
+    /// Counts an object, array or value at the current position, checking the node limit.
+    fn count_node(&mut self) -> Result<(), GonError> {
+        let state = self.state();
+        state.nodes += 1;
+        let nodes = state.nodes;
+        match self.options().max_nodes {
+            Some(max) if nodes > max => Err(self.error(GonErrorKind::TooManyNodes(max))),
+            _ => Ok(())
+        }
+    }
+
+    /// Checks the length of a string that is being parsed against the limit.
+    fn check_string_length(&self, len: usize, start: Position) -> Result<(), GonError> {
+        match self.options().max_string_length {
+            Some(max) if len > max => Err(self.error_at(GonErrorKind::StringTooLong(max), start)),
+            _ => Ok(())
+        }
+    }
+
+    /// Inserts an object entry, handling duplicate keys according to the options. The values of collected duplicates
+    /// are stored along with the index of their key until `collect_duplicates` is called.
+    fn insert_entry<N: Node<'s>>(
+        &self,
+        map: &mut IndexMap<N::Str, N>,
+        collected: &mut Vec<(usize, N)>,
+        key: N::Str,
+        key_pos: Position,
+        val: N
+    ) -> Result<(), GonError> {
+        let Some(index) = map.get_index_of(&key) else {
             map.insert(key, val);
-            self.skip_whitespace_and_token(',');
+            return Ok(());
+        };
+        match self.options().duplicate_keys {
+            DuplicateKeys::Error => {
+                return Err(self.error_at(GonErrorKind::DuplicateKey(key.as_ref().to_owned()), key_pos));
+            }
+            DuplicateKeys::FirstWins => (),
+            DuplicateKeys::LastWins => *map.get_index_mut(index).unwrap().1 = val,
+            DuplicateKeys::Collect => collected.push((index, val))
         }
-        Ok(Gon::Object(map))
+        Ok(())
     }
-    
-    fn parse_val<'a>(&mut self) -> Result<Gon, GonError> {
-        match self.peek() {
-            Some('{') => {
-                self.next();
-                self.skip_whitespace();
-                let val = self.parse_object()?;
-                if !matches!(self.next(), Some('}')) {
-                    return Err(GonError::ClosingBraceExpected);
-                }
-                Ok(val)
-            },
-            Some('[') => {
-                self.next();
-                let mut arr = Vec::new();
-                self.skip_whitespace();
-                loop {
-                    match self.peek() {
-                        Some(']') => {
-                            self.next();
-                            break;
-                        },
-                        None => return Err(GonError::ClosingBracketExpected),
-                        _ => {
-                            arr.push(self.parse_val()?);
-                            self.skip_whitespace_and_token(',');
-                        }
-                    }
+
+    /// Records the error in recovering mode so parsing can go on. Otherwise, and for errors that parsing can't go on
+    /// after, the error is returned. An error at the same position as the previous one is a consequence of it and
+    /// isn't recorded.
+    fn record(&mut self, err: GonError) -> Result<(), GonError> {
+        let fatal = matches!(err.kind, GonErrorKind::TooManyNodes(_) | GonErrorKind::Io(_));
+        match &mut self.state().errors {
+            Some(errors) if !fatal => {
+                if errors.last().is_none_or(|last| last.pos != err.pos) {
+                    errors.push(err);
                 }
-                Ok(Gon::Array(arr))
+                Ok(())
             }
-            Some(_) => self.parse_string().map(|val| Gon::Value(val)),
-            None => Err(GonError::ValueExpected)
+            _ => Err(err)
         }
     }
-    
-    fn parse_string(&mut self) -> Result<String, GonError> {
-        Ok(match self.peek() {
+
+    /// Records the error in recovering mode and skips to the next line or to the closing character of the current
+    /// object or array, so parsing resumes at the next key or element. Nested objects and arrays are skipped as a
+    /// whole.
+    fn recover(&mut self, err: GonError, closing: char) -> Result<(), GonError> {
+        self.record(err)?;
+        let mut nesting = 0usize;
+        while let Some(c) = self.peek() {
+            if nesting == 0 && c == closing {
+                break;
+            }
+            self.next();
+            match c {
+                '{' | '[' => nesting += 1,
+                '}' | ']' => nesting = nesting.saturating_sub(1),
+                '\n' if nesting == 0 => break,
+                _ => ()
+            }
+        }
+        self.skip_whitespace();
+        Ok(())
+    }
+
+    /// An error for an unexpected character at the current position. Comments are reported as such in JSON.
+    fn unexpected(&mut self, kind: GonErrorKind) -> GonError {
+        if self.options().dialect == Dialect::Json && self.peek() == Some('#') {
+            self.error(GonErrorKind::CommentsNotAllowed)
+        } else {
+            self.error(kind)
+        }
+    }
+
+    /// Parses a quoted or unquoted string. The string is borrowed from the source if the parser supports it and the
+    /// string doesn't contain escape sequences.
+    fn parse_string(&mut self) -> Result<Cow<'s, str>, GonError> {
+        let quote_pos = self.pos();
+        let quoted = match self.peek() {
             Some('\"') => {
                 self.next();
-                let mut res = String::new();
-                loop {
-                    match self.next() {
-                        Some('\\') => res.push(self.parse_escape()?),
-                        Some('\"') => break,
-                        Some(c) => res.push(c),
-                        None => return Err(GonError::QuoteExpected)
+                true
+            }
+            Some(_) => false,
+            None => return Err(self.error(GonErrorKind::StringExpected))
+        };
+        let start = self.pos().offset;
+        // Characters are only collected after an escape sequence was found or if the parser can't borrow.
+        let mut owned = self.slice(start..start).is_none().then(String::new);
+        loop {
+            self.check_string_length(owned.as_ref().map_or(self.pos().offset - start, String::len), quote_pos)?;
+            match self.peek() {
+                Some('\\') => {
+                    let escape_offset = self.pos().offset;
+                    let c = self.parse_escape()?;
+                    owned.get_or_insert_with(|| self.slice(start..escape_offset).unwrap().to_owned()).push(c);
+                    continue;
+                }
+                Some('\"') if quoted => break,
+                Some(c) if quoted && c < ' ' && self.options().dialect == Dialect::Json => {
+                    return Err(self.error(GonErrorKind::UnescapedControlCharacter(c)));
+                }
+                Some('{' | '}' | '[' | ']' | ':' | ',') if !quoted => {
+                    if self.pos().offset == start {
+                        return Err(self.error(GonErrorKind::StringExpected));
                     }
+                    break;
                 }
-                res
-            },
-            Some(_) => {
-                let mut res = String::new();
-                loop {
-                    match self.peek() {
-                        Some('\\') => {
-                            self.next();
-                            self.parse_escape()?;
-                        },
-                        Some('{' | '}' |  '[' | ']' | ':' | ',') => break,
-                        Some(c) if is_whitespace(c) => break,
-                        None => break,
-                        Some(_) => res.push(self.next().unwrap())
+                Some(c) if !quoted && is_whitespace(c) => break,
+                None if quoted => return Err(self.error_at(GonErrorKind::QuoteExpected, quote_pos)),
+                None => break,
+                Some(c) => {
+                    self.next();
+                    if let Some(owned) = &mut owned {
+                        owned.push(c);
                     }
                 }
-                res
-            },
-            None => return Err(GonError::StringExpected)
+            }
+        }
+        let end = self.pos().offset;
+        if quoted {
+            self.next();
+        }
+        Ok(match owned {
+            Some(owned) => Cow::Owned(owned),
+            None => Cow::Borrowed(self.slice(start..end).unwrap())
         })
     }
 
+    /// Parses an escape sequence, starting at the backslash.
     fn parse_escape(&mut self) -> Result<char, GonError> {
+        let escape_pos = self.pos();
+        self.next();
         Ok(match self.next() {
             Some('"') => '\"',
             Some('\\') => '\\',
@@ -104,15 +439,73 @@ pub(crate) trait Parser {
             Some('n') => '\n',
             Some('r') => '\r',
             Some('t') => '\t',
-            // Unicode escape codes are supported in json but not supported right now
-            Some('u') => return Err(GonError::HexEscapesNotSupported),
-            Some(c) => return Err(GonError::UnexpectedEscapeCharacter(c)),
-            None => return Err(GonError::EscapeCharacterExpected)
+            Some('u') => {
+                let invalid = |p: &Self| p.error_at(GonErrorKind::InvalidHexEscape, escape_pos);
+                let high = self.parse_hex4().ok_or_else(|| invalid(self))?;
+                match high {
+                    // high surrogate, has to be followed by an escaped low surrogate
+                    0xD800..=0xDBFF => {
+                        if self.next() != Some('\\') || self.next() != Some('u') {
+                            return Err(invalid(self));
+                        }
+                        let low = self.parse_hex4().ok_or_else(|| invalid(self))?;
+                        if !(0xDC00..=0xDFFF).contains(&low) {
+                            return Err(invalid(self));
+                        }
+                        let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                        char::from_u32(code).ok_or_else(|| invalid(self))?
+                    }
+                    // lone low surrogates are invalid and rejected by char::from_u32
+                    _ => char::from_u32(high).ok_or_else(|| invalid(self))?
+                }
+            }
+            Some(c) => return Err(self.error_at(GonErrorKind::UnexpectedEscapeCharacter(c), escape_pos)),
+            None => return Err(self.error_at(GonErrorKind::EscapeCharacterExpected, escape_pos))
         })
     }
 
+    /// Parses the 4 hex digits of a `\u` escape.
+    fn parse_hex4(&mut self) -> Option<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            code = code * 16 + self.next()?.to_digit(16)?;
+        }
+        Some(code)
+    }
+
+    /// Parses a bare JSON value, which has to be a number, `true`, `false` or `null`.
+    fn parse_json_literal(&mut self) -> Result<Cow<'s, str>, GonError> {
+        let start = self.pos();
+        let mut owned = self.slice(start.offset..start.offset).is_none().then(String::new);
+        while let Some(c) = self.peek() {
+            self.check_string_length(owned.as_ref().map_or(self.pos().offset - start.offset, String::len), start)?;
+            if is_whitespace(c) || matches!(c, '{' | '}' | '[' | ']' | ':' | ',' | '"') {
+                break;
+            }
+            self.next();
+            if let Some(owned) = &mut owned {
+                owned.push(c);
+            }
+        }
+        let token = match owned {
+            Some(owned) => Cow::Owned(owned),
+            None => Cow::Borrowed(self.slice(start.offset..self.pos().offset).unwrap())
+        };
+        if !GonValue::bare(&*token).is_string() {
+            return Ok(token);
+        }
+        let kind = match token.chars().next() {
+            None => GonErrorKind::ValueExpected,
+            Some('#') => GonErrorKind::CommentsNotAllowed,
+            Some('-' | '+' | '.' | '0'..='9') => GonErrorKind::InvalidNumber,
+            Some(_) => GonErrorKind::UnquotedString
+        };
+        Err(self.error_at(kind, start))
+    }
+
     fn skip_whitespace(&mut self) {
-        while self.peek().map_or(false, |c| is_whitespace(c) || c == '#') {
+        let comments = self.options().dialect == Dialect::Gon;
+        while self.peek().is_some_and(|c| is_whitespace(c) || (comments && c == '#')) {
             let c = self.next().unwrap();
             if c == '#' {
                 loop {
@@ -137,19 +530,159 @@ pub(crate) trait Parser {
     }
 }
 
-pub(crate) struct StrParser<'p>(Peekable<Chars<'p>>);
+pub(crate) struct StrParser<'p> {
+    src: &'p str,
+    pos: Position,
+    options: ParseOptions,
+    state: ParseState
+}
 impl<'p> StrParser<'p> {
-    pub(crate) fn new(s: &'p str) -> Self {
-        Self(s.chars().peekable())
+    pub(crate) fn new(src: &'p str) -> Self {
+        Self::with_options(src, ParseOptions::default())
+    }
+
+    pub(crate) fn with_options(src: &'p str, options: ParseOptions) -> Self {
+        Self { src, pos: Position::default(), options, state: ParseState::default() }
+    }
+}
+
+impl<'p> Parser<'p> for StrParser<'p> {
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos.offset += c.len_utf8();
+        if c == '\n' {
+            self.pos.line += 1;
+            self.pos.column = 1;
+        } else {
+            self.pos.column += 1;
+        }
+        Some(c)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.src[self.pos.offset..].chars().next()
+    }
+
+    fn pos(&self) -> Position {
+        self.pos
+    }
+
+    fn line_at(&self, pos: Position) -> Option<String> {
+        let start = self.src[..pos.offset].rfind('\n').map_or(0, |i| i + 1);
+        let end = self.src[pos.offset..].find('\n').map_or(self.src.len(), |i| pos.offset + i);
+        Some(self.src[start..end].trim_end_matches('\r').to_owned())
+    }
+
+    fn slice(&self, range: Range<usize>) -> Option<&'p str> {
+        Some(&self.src[range])
+    }
+
+    fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    fn state(&mut self) -> &mut ParseState {
+        &mut self.state
+    }
+}
+
+/// Parses from a buffered reader, decoding UTF-8 one character at a time.
+pub(crate) struct ReadParser<R> {
+    reader: R,
+    peeked: Option<char>,
+    pos: Position,
+    /// The current line up to the current position, used for error snippets.
+    line: String,
+    source_error: Option<GonError>,
+    options: ParseOptions,
+    state: ParseState
+}
+impl<R: BufRead> ReadParser<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self::with_options(reader, ParseOptions::default())
+    }
+
+    pub(crate) fn with_options(reader: R, options: ParseOptions) -> Self {
+        Self { reader, peeked: None, pos: Position::default(), line: String::new(), source_error: None, options, state: ParseState::default() }
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            match self.reader.fill_buf() {
+                Ok(buf) => {
+                    let byte = buf.first().copied();
+                    if byte.is_some() {
+                        self.reader.consume(1);
+                    }
+                    return Ok(byte);
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err)
+            }
+        }
+    }
+
+    fn decode(&mut self) -> Result<Option<char>, GonErrorKind> {
+        let Some(first) = self.read_byte().map_err(GonErrorKind::Io)? else { return Ok(None) };
+        let width = match first {
+            0x00..=0x7F => return Ok(Some(first as char)),
+            0xC2..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF4 => 4,
+            _ => return Err(GonErrorKind::InvalidUtf8)
+        };
+        let mut bytes = [first, 0, 0, 0];
+        for byte in &mut bytes[1..width] {
+            *byte = self.read_byte().map_err(GonErrorKind::Io)?.ok_or(GonErrorKind::InvalidUtf8)?;
+        }
+        let s = std::str::from_utf8(&bytes[..width]).map_err(|_| GonErrorKind::InvalidUtf8)?;
+        Ok(s.chars().next())
     }
 }
 
-impl<'p> Parser for StrParser<'p> {
+impl<'s, R: BufRead> Parser<'s> for ReadParser<R> {
     fn next(&mut self) -> Option<char> {
-        self.0.next()
+        let c = self.peek()?;
+        self.peeked = None;
+        self.pos.offset += c.len_utf8();
+        if c == '\n' {
+            self.pos.line += 1;
+            self.pos.column = 1;
+            self.line.clear();
+        } else {
+            self.pos.column += 1;
+            self.line.push(c);
+        }
+        Some(c)
     }
 
     fn peek(&mut self) -> Option<char> {
-        self.0.peek().map(|c| *c)
+        if self.peeked.is_none() && self.source_error.is_none() {
+            match self.decode() {
+                Ok(c) => self.peeked = c,
+                Err(kind) => self.source_error = Some(self.error(kind))
+            }
+        }
+        self.peeked
     }
-}
\ No newline at end of file
+
+    fn pos(&self) -> Position {
+        self.pos
+    }
+
+    fn line_at(&self, pos: Position) -> Option<String> {
+        (pos.line == self.pos.line).then(|| self.line.trim_end_matches('\r').to_owned())
+    }
+
+    fn take_error(&mut self) -> Option<GonError> {
+        self.source_error.take()
+    }
+
+    fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    fn state(&mut self) -> &mut ParseState {
+        &mut self.state
+    }
+}
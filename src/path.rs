@@ -0,0 +1,289 @@
+//! Looking up nested GON by path, either as a JSON Pointer (RFC 6901) like `/big_factory/locations/2/name` or with
+//! dots and brackets like `big_factory.locations[2].name`.
+
+use std::{borrow::Cow, convert::Infallible};
+
+use crate::{Gon, GonGetError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathErrorKind {
+    /// The path itself is malformed
+    InvalidSyntax,
+    /// The object doesn't contain the key
+    MissingKey(String),
+    /// The array is shorter than the index
+    IndexOutOfBounds(usize),
+    /// A segment that isn't an index was used on an array
+    InvalidIndex(String),
+    /// Tried to index into a value
+    UnexpectedValue,
+    /// Tried to use a bracket index on an object
+    UnexpectedObject,
+    UnexpectedArray
+}
+impl std::fmt::Display for PathErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidSyntax => write!(f, "invalid path syntax"),
+            Self::MissingKey(key) => write!(f, "key '{key}' is missing"),
+            Self::IndexOutOfBounds(index) => write!(f, "index {index} is out of bounds"),
+            Self::InvalidIndex(segment) => write!(f, "'{segment}' is not a valid array index"),
+            Self::UnexpectedValue => write!(f, "tried to index into a value"),
+            Self::UnexpectedObject => write!(f, "tried to index into an object with an array index"),
+            Self::UnexpectedArray => write!(f, "tried to index into an array with a key")
+        }
+    }
+}
+impl From<GonGetError<Infallible>> for PathErrorKind {
+    fn from(err: GonGetError<Infallible>) -> Self {
+        match err {
+            GonGetError::UnexpectedObject => Self::UnexpectedObject,
+            GonGetError::UnexpectedArray => Self::UnexpectedArray,
+            GonGetError::UnexpectedValue => Self::UnexpectedValue,
+            GonGetError::IndexOutOfBounds(index) => Self::IndexOutOfBounds(index),
+            GonGetError::ConversionFailed(never) => match never {}
+        }
+    }
+}
+
+/// An error when looking up a path, naming the segment that failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathError {
+    /// The full path that was looked up
+    pub path: String,
+    /// The index of the failing segment, starting at 0
+    pub segment: usize,
+    pub kind: PathErrorKind
+}
+impl std::fmt::Display for PathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at segment {} of path '{}'", self.kind, self.segment, self.path)
+    }
+}
+impl std::error::Error for PathError { }
+
+enum Segment<'p> {
+    /// A key for objects, also used as an index for arrays if it is a number
+    Name(Cow<'p, str>),
+    /// An index in brackets, only valid for arrays
+    Index(usize)
+}
+
+/// Parses an array index the way RFC 6901 does: only digits without leading zeros.
+fn parse_index(s: &str) -> Option<usize> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) || (s.len() > 1 && s.starts_with('0')) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn syntax_error(path: &str, segment: usize) -> PathError {
+    PathError { path: path.to_owned(), segment, kind: PathErrorKind::InvalidSyntax }
+}
+
+fn pointer_segments(pointer: &str) -> Result<Vec<Segment<'_>>, PathError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    // RFC 6901 only allows the empty pointer for the whole document or a pointer starting with a slash
+    let Some(segments) = pointer.strip_prefix('/') else {
+        return Err(syntax_error(pointer, 0));
+    };
+    segments.split('/').enumerate().map(|(i, segment)| {
+        if !segment.contains('~') {
+            return Ok(Segment::Name(Cow::Borrowed(segment)));
+        }
+        let mut name = String::with_capacity(segment.len());
+        let mut chars = segment.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '~' => match chars.next() {
+                    Some('0') => name.push('~'),
+                    Some('1') => name.push('/'),
+                    _ => return Err(syntax_error(pointer, i))
+                }
+                c => name.push(c)
+            }
+        }
+        Ok(Segment::Name(Cow::Owned(name)))
+    }).collect()
+}
+
+fn dotted_segments(path: &str) -> Result<Vec<Segment<'_>>, PathError> {
+    let mut segments = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        let segment = segments.len();
+        let err = || syntax_error(path, segment);
+        if let Some(bracketed) = rest.strip_prefix('[') {
+            if let Some(quoted) = bracketed.strip_prefix('"') {
+                // a quoted key, which can contain dots and brackets
+                let mut name = String::new();
+                let mut chars = quoted.char_indices();
+                let end = loop {
+                    match chars.next().ok_or_else(err)? {
+                        (_, '\\') => name.push(chars.next().ok_or_else(err)?.1),
+                        (i, '"') => break i,
+                        (_, c) => name.push(c)
+                    }
+                };
+                rest = quoted[end + 1..].strip_prefix(']').ok_or_else(err)?;
+                segments.push(Segment::Name(Cow::Owned(name)));
+            } else {
+                let (index, after) = bracketed.split_once(']').ok_or_else(err)?;
+                segments.push(Segment::Index(parse_index(index).ok_or_else(err)?));
+                rest = after;
+            }
+        } else {
+            let name_start = if segment == 0 { rest } else { rest.strip_prefix('.').ok_or_else(err)? };
+            let end = name_start.find(['.', '[']).unwrap_or(name_start.len());
+            if end == 0 {
+                return Err(err());
+            }
+            segments.push(Segment::Name(Cow::Borrowed(&name_start[..end])));
+            rest = &name_start[end..];
+        }
+    }
+    Ok(segments)
+}
+
+fn step<'g>(gon: &'g Gon, segment: &Segment) -> Result<&'g Gon, PathErrorKind> {
+    match segment {
+        Segment::Name(name) if matches!(gon, Gon::Array(_)) => {
+            let index = parse_index(name).ok_or_else(|| PathErrorKind::InvalidIndex(name.to_string()))?;
+            Ok(gon.try_index(index)?)
+        }
+        Segment::Name(name) => gon.try_value(name)?.ok_or_else(|| PathErrorKind::MissingKey(name.to_string())),
+        Segment::Index(index) => Ok(gon.try_index(*index)?)
+    }
+}
+
+fn step_mut<'g>(gon: &'g mut Gon, segment: &Segment) -> Result<&'g mut Gon, PathErrorKind> {
+    match segment {
+        Segment::Name(name) if matches!(gon, Gon::Array(_)) => {
+            let index = parse_index(name).ok_or_else(|| PathErrorKind::InvalidIndex(name.to_string()))?;
+            Ok(gon.try_index_mut(index)?)
+        }
+        Segment::Name(name) => gon.try_value_mut(name)?.ok_or_else(|| PathErrorKind::MissingKey(name.to_string())),
+        Segment::Index(index) => Ok(gon.try_index_mut(*index)?)
+    }
+}
+
+fn resolve<'g>(gon: &'g Gon, path: &str, segments: Vec<Segment>) -> Result<&'g Gon, PathError> {
+    segments.iter().enumerate().try_fold(gon, |gon, (i, segment)| {
+        step(gon, segment).map_err(|kind| PathError { path: path.to_owned(), segment: i, kind })
+    })
+}
+
+fn resolve_mut<'g>(gon: &'g mut Gon, path: &str, segments: Vec<Segment>) -> Result<&'g mut Gon, PathError> {
+    segments.iter().enumerate().try_fold(gon, |gon, (i, segment)| {
+        step_mut(gon, segment).map_err(|kind| PathError { path: path.to_owned(), segment: i, kind })
+    })
+}
+
+impl Gon {
+    /// Looks up a JSON Pointer (RFC 6901) like `/big_factory/locations/2/name`. Pointers other than the empty one
+    /// have to start with a slash.
+    /// Returns None if any segment can't be resolved, use `try_pointer` to find out why.
+    pub fn pointer(&self, pointer: &str) -> Option<&Gon> {
+        self.try_pointer(pointer).ok()
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Gon> {
+        self.try_pointer_mut(pointer).ok()
+    }
+
+    pub fn try_pointer(&self, pointer: &str) -> Result<&Gon, PathError> {
+        resolve(self, pointer, pointer_segments(pointer)?)
+    }
+
+    pub fn try_pointer_mut(&mut self, pointer: &str) -> Result<&mut Gon, PathError> {
+        resolve_mut(self, pointer, pointer_segments(pointer)?)
+    }
+
+    /// Looks up a path like `big_factory.locations[2].name`. Keys containing dots or brackets can be quoted:
+    /// `factory["v1.2"]`. Returns None if any segment can't be resolved, use `try_path` to find out why.
+    pub fn path(&self, path: &str) -> Option<&Gon> {
+        self.try_path(path).ok()
+    }
+
+    pub fn path_mut(&mut self, path: &str) -> Option<&mut Gon> {
+        self.try_path_mut(path).ok()
+    }
+
+    pub fn try_path(&self, path: &str) -> Result<&Gon, PathError> {
+        resolve(self, path, dotted_segments(path)?)
+    }
+
+    pub fn try_path_mut(&mut self, path: &str) -> Result<&mut Gon, PathError> {
+        resolve_mut(self, path, dotted_segments(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Gon;
+    use super::{PathError, PathErrorKind};
+
+    fn example() -> Gon {
+        Gon::parse(r#"
+            big_factory {
+                locations [
+                    { name "New York City" }
+                    { name Basement }
+                    { name "Tyler's House" }
+                ]
+                "a/b~c" slashes
+                "v1.2" dotted
+            }
+            "" empty
+        "#).unwrap()
+    }
+
+    #[test]
+    fn lookups() {
+        let gon = example();
+        assert_eq!(gon.pointer("/big_factory/locations/2/name").unwrap().str(), "Tyler's House");
+        assert_eq!(gon.path("big_factory.locations[2].name").unwrap().str(), "Tyler's House");
+        assert_eq!(gon.path("big_factory.locations.1.name").unwrap().str(), "Basement");
+        assert_eq!(gon.pointer("/big_factory/a~1b~0c").unwrap().str(), "slashes");
+        assert_eq!(gon.path(r#"big_factory["v1.2"]"#).unwrap().str(), "dotted");
+        assert_eq!(gon.path(r#"["big_factory"]["a/b~c"]"#).unwrap().str(), "slashes");
+        assert_eq!(gon.pointer("/").unwrap().str(), "empty");
+        assert_eq!(gon.pointer(""), Some(&gon));
+        assert_eq!(gon.path(""), Some(&gon));
+
+        let mut gon = gon;
+        *gon.path_mut("big_factory.locations[0].name").unwrap() = Gon::Value("Boston".into());
+        gon.pointer_mut("/big_factory/locations/1").unwrap().insert("size", Gon::Value("small".into()));
+        assert_eq!(gon["big_factory"]["locations"][0]["name"].str(), "Boston");
+        assert_eq!(gon.pointer("/big_factory/locations/1/size").unwrap().str(), "small");
+    }
+
+    #[test]
+    fn errors() {
+        let gon = example();
+        let err = |path: &str| gon.try_path(path).unwrap_err();
+        assert_eq!(err("big_factory.location[2]"), PathError {
+            path: "big_factory.location[2]".to_owned(),
+            segment: 1,
+            kind: PathErrorKind::MissingKey("location".to_owned())
+        });
+        assert_eq!(err("big_factory.locations[5]").kind, PathErrorKind::IndexOutOfBounds(5));
+        assert_eq!(err("big_factory.locations.first").kind, PathErrorKind::InvalidIndex("first".to_owned()));
+        assert_eq!(err("big_factory.locations.01").kind, PathErrorKind::InvalidIndex("01".to_owned()));
+        assert_eq!(err("big_factory[0]").kind, PathErrorKind::UnexpectedObject);
+        let value_err = err("big_factory.locations[1].name.first");
+        assert_eq!((value_err.segment, value_err.kind), (4, PathErrorKind::UnexpectedValue));
+        for invalid in ["a..b", "a.", ".a", "a[", "a[x]", "a[1", r#"a["b"#, r#"a["b"x"#] {
+            assert_eq!(err(invalid).kind, PathErrorKind::InvalidSyntax, "{invalid}");
+        }
+        assert_eq!(gon.try_pointer("/big_factory/~2").unwrap_err().kind, PathErrorKind::InvalidSyntax);
+        let relative = gon.try_pointer("big_factory/locations").unwrap_err();
+        assert_eq!((relative.segment, relative.kind), (0, PathErrorKind::InvalidSyntax));
+        assert_eq!(
+            gon.try_pointer("/big_factory/locations/9").unwrap_err().to_string(),
+            "index 9 is out of bounds at segment 2 of path '/big_factory/locations/9'"
+        );
+    }
+}
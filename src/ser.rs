@@ -0,0 +1,400 @@
+//! serde serialization to GON, only available with the `serde` feature.
+
+use serde::{ser::{self, Impossible, SerializeMap, SerializeSeq}, Serialize};
+
+use crate::{writer::{self, WriteStyle}, Gon, GonValue, IndexMap};
+
+#[derive(Debug)]
+pub enum SerializeError {
+    /// Object keys have to be serialized as strings, numbers, chars or bools.
+    KeyMustBeAString,
+    Custom(String)
+}
+impl std::fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+impl std::error::Error for SerializeError { }
+
+impl ser::Error for SerializeError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+/// Serializes the value into a GON tree.
+pub fn to_gon<T: Serialize + ?Sized>(value: &T) -> Result<Gon, SerializeError> {
+    value.serialize(Serializer)
+}
+
+/// Serializes the value as compact GON text.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, SerializeError> {
+    Ok(writer::write(&to_gon(value)?, WriteStyle::Compact))
+}
+
+/// Serializes the value as pretty GON text.
+pub fn to_string_pretty<T: Serialize + ?Sized>(value: &T) -> Result<String, SerializeError> {
+    Ok(writer::write(&to_gon(value)?, WriteStyle::PRETTY))
+}
+
+/// Values are serialized with their JSON type, so `12` becomes a number while `"12"` stays a string.
+impl Serialize for Gon {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Gon::Value(val) if val.is_null() => serializer.serialize_none(),
+            Gon::Value(val) if val.is_bool() => serializer.serialize_bool(val.as_str() == "true"),
+            Gon::Value(val) if val.is_int() => match (val.parse::<i64>(), val.parse::<u64>()) {
+                (Ok(i), _) => serializer.serialize_i64(i),
+                (_, Ok(u)) => serializer.serialize_u64(u),
+                _ => serializer.serialize_str(val)
+            }
+            Gon::Value(val) if val.is_float() => match val.parse::<f64>() {
+                Ok(f) if f.is_finite() => serializer.serialize_f64(f),
+                _ => serializer.serialize_str(val)
+            }
+            Gon::Value(val) => serializer.serialize_str(val),
+            Gon::Array(arr) => {
+                let mut seq = serializer.serialize_seq(Some(arr.len()))?;
+                for val in arr {
+                    seq.serialize_element(val)?;
+                }
+                seq.end()
+            }
+            Gon::Object(map) => {
+                let mut ser_map = serializer.serialize_map(Some(map.len()))?;
+                for (key, val) in map {
+                    ser_map.serialize_entry(key, val)?;
+                }
+                ser_map.end()
+            }
+        }
+    }
+}
+
+/// A serializer producing a `Gon` tree. Enums are externally tagged, `None` is written as `null` and units as empty
+/// objects, matching the deserializer.
+pub struct Serializer;
+
+macro_rules! serialize_display {
+    ($($method: ident $t: ty),*) => {
+        $(
+            fn $method(self, v: $t) -> Result<Gon, SerializeError> {
+                Ok(Gon::Value(v.to_string().into()))
+            }
+        )*
+    };
+}
+
+/// Writes a float so it keeps a fraction or an exponent and isn't read back as an integer, like `1.0` instead of `1`.
+pub(crate) fn float_text(v: impl std::fmt::Display) -> String {
+    let mut text = v.to_string();
+    if text.bytes().all(|b| b.is_ascii_digit() || b == b'-') {
+        text.push_str(".0");
+    }
+    text
+}
+
+fn variant(variant: &'static str, content: Gon) -> Gon {
+    Gon::Object(IndexMap::from([(variant.to_owned(), content)]))
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Gon;
+    type Error = SerializeError;
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeArray;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeObject;
+
+    serialize_display!(
+        serialize_bool bool, serialize_i8 i8, serialize_i16 i16, serialize_i32 i32, serialize_i64 i64,
+        serialize_i128 i128, serialize_u8 u8, serialize_u16 u16, serialize_u32 u32, serialize_u64 u64,
+        serialize_u128 u128
+    );
+
+    fn serialize_f32(self, v: f32) -> Result<Gon, SerializeError> {
+        Ok(Gon::Value(float_text(v).into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Gon, SerializeError> {
+        Ok(Gon::Value(float_text(v).into()))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Gon, SerializeError> {
+        Ok(Gon::Value(GonValue::string(v.to_string())))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Gon, SerializeError> {
+        Ok(Gon::Value(GonValue::string(v.to_owned())))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Gon, SerializeError> {
+        Ok(Gon::Array(v.iter().map(|b| Gon::Value(b.to_string().into())).collect()))
+    }
+
+    fn serialize_none(self) -> Result<Gon, SerializeError> {
+        Ok(Gon::Value("null".into()))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Gon, SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Gon, SerializeError> {
+        Ok(Gon::Object(IndexMap::new()))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Gon, SerializeError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<Gon, SerializeError> {
+        Ok(Gon::Value(variant.into()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<Gon, SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant_name: &'static str,
+        value: &T
+    ) -> Result<Gon, SerializeError> {
+        Ok(variant(variant_name, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, SerializeError> {
+        Ok(SerializeArray { arr: Vec::with_capacity(len.unwrap_or(0)), variant: None })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, SerializeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeArray, SerializeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize
+    ) -> Result<SerializeArray, SerializeError> {
+        Ok(SerializeArray { arr: Vec::with_capacity(len), variant: Some(variant) })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeObject, SerializeError> {
+        Ok(SerializeObject { map: IndexMap::with_capacity(len.unwrap_or(0)), key: None, variant: None })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeObject, SerializeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize
+    ) -> Result<SerializeObject, SerializeError> {
+        Ok(SerializeObject { map: IndexMap::with_capacity(len), key: None, variant: Some(variant) })
+    }
+}
+
+pub struct SerializeArray {
+    arr: Vec<Gon>,
+    variant: Option<&'static str>
+}
+impl SerializeArray {
+    fn finish(self) -> Gon {
+        let arr = Gon::Array(self.arr);
+        match self.variant {
+            Some(name) => variant(name, arr),
+            None => arr
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Gon;
+    type Error = SerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializeError> {
+        self.arr.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Gon, SerializeError> {
+        Ok(self.finish())
+    }
+}
+
+macro_rules! forward_seq_impls {
+    ($($trait: ident $method: ident),*) => {
+        $(
+            impl ser::$trait for SerializeArray {
+                type Ok = Gon;
+                type Error = SerializeError;
+
+                fn $method<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializeError> {
+                    ser::SerializeSeq::serialize_element(self, value)
+                }
+
+                fn end(self) -> Result<Gon, SerializeError> {
+                    Ok(self.finish())
+                }
+            }
+        )*
+    };
+}
+
+forward_seq_impls!(SerializeTuple serialize_element, SerializeTupleStruct serialize_field, SerializeTupleVariant serialize_field);
+
+pub struct SerializeObject {
+    map: IndexMap<String, Gon>,
+    key: Option<String>,
+    variant: Option<&'static str>
+}
+impl SerializeObject {
+    fn finish(self) -> Gon {
+        let obj = Gon::Object(self.map);
+        match self.variant {
+            Some(name) => variant(name, obj),
+            None => obj
+        }
+    }
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = Gon;
+    type Error = SerializeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerializeError> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializeError> {
+        let key = self.key.take().expect("serialize_value called before serialize_key");
+        self.map.insert(key, value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Gon, SerializeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = Gon;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), SerializeError> {
+        self.map.insert(key.to_owned(), value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Gon, SerializeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeObject {
+    type Ok = Gon;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), SerializeError> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<Gon, SerializeError> {
+        Ok(self.finish())
+    }
+}
+
+/// Serializes object keys, which have to be strings or types that are written as single values.
+struct KeySerializer;
+
+macro_rules! serialize_key_display {
+    ($($method: ident $t: ty),*) => {
+        $(
+            fn $method(self, v: $t) -> Result<String, SerializeError> {
+                Ok(v.to_string())
+            }
+        )*
+    };
+}
+
+macro_rules! reject_keys {
+    ($($method: ident($($arg: ty),*) -> $ret: ty),*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ret, SerializeError> {
+                Err(SerializeError::KeyMustBeAString)
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = SerializeError;
+    type SerializeSeq = Impossible<String, SerializeError>;
+    type SerializeTuple = Impossible<String, SerializeError>;
+    type SerializeTupleStruct = Impossible<String, SerializeError>;
+    type SerializeTupleVariant = Impossible<String, SerializeError>;
+    type SerializeMap = Impossible<String, SerializeError>;
+    type SerializeStruct = Impossible<String, SerializeError>;
+    type SerializeStructVariant = Impossible<String, SerializeError>;
+
+    serialize_key_display!(
+        serialize_bool bool, serialize_i8 i8, serialize_i16 i16, serialize_i32 i32, serialize_i64 i64,
+        serialize_i128 i128, serialize_u8 u8, serialize_u16 u16, serialize_u32 u32, serialize_u64 u64,
+        serialize_u128 u128, serialize_f32 f32, serialize_f64 f64, serialize_char char, serialize_str &str
+    );
+
+    reject_keys!(
+        serialize_bytes(&[u8]) -> String,
+        serialize_none() -> String,
+        serialize_unit() -> String,
+        serialize_unit_struct(&'static str) -> String,
+        serialize_seq(Option<usize>) -> Self::SerializeSeq,
+        serialize_tuple(usize) -> Self::SerializeTuple,
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct,
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant,
+        serialize_map(Option<usize>) -> Self::SerializeMap,
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct,
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant
+    );
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String, SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<String, SerializeError> {
+        Ok(variant.to_owned())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<String, SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T
+    ) -> Result<String, SerializeError> {
+        Err(SerializeError::KeyMustBeAString)
+    }
+}
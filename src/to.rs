@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+
+use crate::{Gon, GonValue};
+
+pub trait ToGon {
+    fn to_gon(&self) -> Gon;
+}
+
+macro_rules! display_impls {
+    ($($t: ty)*) => {
+        $(
+            impl ToGon for $t {
+                fn to_gon(&self) -> Gon {
+                    Gon::Value(self.to_string().into())
+                }
+            }
+        )*
+    };
+}
+
+display_impls!(u8 u16 u32 u64 u128 i8 i16 i32 i64 i128);
+
+impl ToGon for String {
+    fn to_gon(&self) -> Gon {
+        Gon::Value(GonValue::string(self.clone()))
+    }
+}
+
+impl ToGon for str {
+    fn to_gon(&self) -> Gon {
+        Gon::Value(GonValue::string(self.to_owned()))
+    }
+}
+
+impl<T: ToGon + ?Sized> ToGon for &T {
+    fn to_gon(&self) -> Gon {
+        T::to_gon(self)
+    }
+}
+
+impl<T: ToGon, const N: usize> ToGon for [T; N] {
+    fn to_gon(&self) -> Gon {
+        Gon::Array(self.iter().map(T::to_gon).collect())
+    }
+}
+
+impl<T: ToGon> ToGon for Vec<T> {
+    fn to_gon(&self) -> Gon {
+        Gon::Array(self.iter().map(T::to_gon).collect())
+    }
+}
+
+/// `None` is written as a bare `null`.
+impl<T: ToGon> ToGon for Option<T> {
+    fn to_gon(&self) -> Gon {
+        match self {
+            Some(val) => val.to_gon(),
+            None => Gon::Value("null".into())
+        }
+    }
+}
+
+impl ToGon for Gon {
+    fn to_gon(&self) -> Gon {
+        self.clone()
+    }
+}
+
+/// The keys are sorted, so the output doesn't depend on the random order of the map.
+impl<T: ToGon> ToGon for HashMap<String, T> {
+    fn to_gon(&self) -> Gon {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+        Gon::Object(entries.into_iter().map(|(key, val)| (key.clone(), val.to_gon())).collect())
+    }
+}
+
+impl<T: ToGon> ToGon for IndexMap<String, T> {
+    fn to_gon(&self) -> Gon {
+        Gon::Object(self.iter().map(|(key, val)| (key.clone(), val.to_gon())).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{Gon, from::FromGon};
+    use super::ToGon;
+
+    #[test]
+    fn round_trip() {
+        let map = HashMap::from([
+            ("a".to_owned(), vec![[1, 2], [-3, 4]]),
+            ("b".to_owned(), vec![])
+        ]);
+        let gon = map.to_gon();
+        assert_eq!(gon["a"][1][0].get::<i32>(), -3);
+        assert_eq!(HashMap::<String, Vec<[i32; 2]>>::from_gon(&gon).unwrap(), map);
+        assert_eq!(Gon::parse(&gon.to_string()).unwrap(), gon);
+        let gon = HashMap::from([("c".to_owned(), 1), ("a".to_owned(), 2), ("b".to_owned(), 3)]).to_gon();
+        let Gon::Object(entries) = &gon else { panic!("Expected object") };
+        assert_eq!(entries.keys().collect::<Vec<_>>(), ["a", "b", "c"]);
+
+        assert_eq!("hello world".to_gon(), Gon::Value("hello world".into()));
+        assert_eq!(String::from_gon(&"hello world".to_gon()).unwrap(), "hello world");
+    }
+}
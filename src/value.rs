@@ -0,0 +1,184 @@
+//! The text of a GON value along with whether it was quoted, which is what separates the string `"123"` from the
+//! number `123`. Bare values are classified following the JSON rules, so `true`, `false`, `null` and JSON numbers have
+//! their JSON types and everything else is a string.
+
+use std::{borrow::Cow, fmt, ops::Deref};
+
+#[derive(Debug, Clone, Default)]
+pub struct GonValue<S = String> {
+    pub text: S,
+    /// If the value was written in quotes. Quoted values are always strings.
+    pub quoted: bool
+}
+
+impl<S: Deref<Target = str>> GonValue<S> {
+    /// A bare value, which is classified by its text.
+    pub fn bare(text: S) -> Self {
+        Self { text, quoted: false }
+    }
+
+    /// A string value. It is only marked as quoted if the text would otherwise be classified as something else, so
+    /// writing it won't add unnecessary quotes.
+    pub fn string(text: S) -> Self {
+        let quoted = !is_string_literal(&text);
+        Self { text, quoted }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// A bare JSON number without a fraction or exponent, like `-12`.
+    pub fn is_int(&self) -> bool {
+        !self.quoted && number_kind(&self.text) == Some(false)
+    }
+
+    /// A bare JSON number with a fraction or exponent, like `1.5` or `2e10`.
+    pub fn is_float(&self) -> bool {
+        !self.quoted && number_kind(&self.text) == Some(true)
+    }
+
+    /// A bare `true` or `false`.
+    pub fn is_bool(&self) -> bool {
+        !self.quoted && matches!(&*self.text, "true" | "false")
+    }
+
+    /// A bare `null`.
+    pub fn is_null(&self) -> bool {
+        !self.quoted && &*self.text == "null"
+    }
+
+    /// Anything that isn't a number, bool or null.
+    pub fn is_string(&self) -> bool {
+        self.quoted || is_string_literal(&self.text)
+    }
+}
+
+impl<'a> GonValue<Cow<'a, str>> {
+    pub fn into_owned(self) -> GonValue {
+        GonValue { text: self.text.into_owned(), quoted: self.quoted }
+    }
+}
+
+/// Checks if bare text would be classified as a string.
+fn is_string_literal(s: &str) -> bool {
+    !matches!(s, "true" | "false" | "null") && number_kind(s).is_none()
+}
+
+/// Checks the text against the JSON number grammar and returns if it has a fraction or exponent.
+fn number_kind(s: &str) -> Option<bool> {
+    let b = s.as_bytes();
+    let digits = |i: usize| b[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let mut i = usize::from(b.first() == Some(&b'-'));
+    match digits(i) {
+        0 => return None,
+        // no leading zeros
+        n if n > 1 && b[i] == b'0' => return None,
+        n => i += n
+    }
+    let mut float = false;
+    if b.get(i) == Some(&b'.') {
+        match digits(i + 1) {
+            0 => return None,
+            n => i += 1 + n
+        }
+        float = true;
+    }
+    if matches!(b.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(b.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        match digits(i) {
+            0 => return None,
+            n => i += n
+        }
+        float = true;
+    }
+    (i == b.len()).then_some(float)
+}
+
+/// Values are equal if they have the same text and both are strings or both are not, so the quotes of `"abc"` don't
+/// matter but those of `"123"` do.
+impl<S: Deref<Target = str>, T: Deref<Target = str>> PartialEq<GonValue<T>> for GonValue<S> {
+    fn eq(&self, other: &GonValue<T>) -> bool {
+        *self.text == *other.text && self.is_string() == other.is_string()
+    }
+}
+impl<S: Deref<Target = str>> Eq for GonValue<S> { }
+
+impl<S: Deref<Target = str>> PartialEq<str> for GonValue<S> {
+    fn eq(&self, other: &str) -> bool {
+        &*self.text == other
+    }
+}
+impl<S: Deref<Target = str>> PartialEq<&str> for GonValue<S> {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.text == *other
+    }
+}
+
+impl<S: Deref<Target = str>> Deref for GonValue<S> {
+    type Target = str;
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl<S: Deref<Target = str>> AsRef<str> for GonValue<S> {
+    fn as_ref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl<S: Deref<Target = str>> fmt::Display for GonValue<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl From<String> for GonValue {
+    fn from(text: String) -> Self {
+        Self::bare(text)
+    }
+}
+impl From<&str> for GonValue {
+    fn from(text: &str) -> Self {
+        Self::bare(text.to_owned())
+    }
+}
+impl<'a> From<&'a str> for GonValue<Cow<'a, str>> {
+    fn from(text: &'a str) -> Self {
+        Self::bare(Cow::Borrowed(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Gon;
+    use super::GonValue;
+
+    #[test]
+    fn classify() {
+        let gon = Gon::parse(r#"[12 -0 "12" 1.5 -2e10 1E+2 01 1. .5 - 1e true "false" null nil abc]"#).unwrap();
+        let Gon::Array(arr) = &gon else { panic!("Expected array") };
+        let kinds: Vec<&str> = arr.iter().map(|val| match val {
+            val if val.is_int() => "int",
+            val if val.is_float() => "float",
+            val if val.is_bool() => "bool",
+            val if val.is_null() => "null",
+            _ => "string"
+        }).collect();
+        assert_eq!(kinds, [
+            "int", "int", "string", "float", "float", "float", "string", "string", "string", "string", "string",
+            "bool", "string", "null", "string", "string"
+        ]);
+
+        assert!(arr[2].is_quoted());
+        assert_ne!(arr[0], arr[2]);
+        assert_eq!(Gon::parse(r#""abc""#).unwrap(), Gon::Value("abc".into()));
+        assert_eq!(GonValue::string("true".to_owned()), GonValue { text: "true".to_owned(), quoted: true });
+        assert_eq!(GonValue::string("abc".to_owned()), GonValue { text: "abc".to_owned(), quoted: false });
+        assert!(!Gon::Array(Vec::new()).is_string());
+    }
+}
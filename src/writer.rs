@@ -0,0 +1,216 @@
+use std::fmt::{self, Write};
+
+use indexmap::IndexMap;
+
+use crate::Gon;
+
+/// The formatting used when writing GON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteStyle {
+    /// Everything is written on a single line, separated by single spaces.
+    Compact,
+    /// Every object entry is written on its own line and nested objects are indented by the given amount of spaces.
+    /// Arrays only containing values are kept on a single line.
+    Pretty { indent: usize }
+}
+impl WriteStyle {
+    /// Pretty style with an indentation of 4 spaces.
+    pub const PRETTY: Self = Self::Pretty { indent: 4 };
+}
+
+/// Writes the GON as a string in the provided style.
+/// Parsing the output using `Gon::parse` will result in the same GON again, as long as the GON isn't nested deeper than
+/// `ParseOptions::DEFAULT_MAX_DEPTH`. Deeper trees can be parsed with a larger `ParseOptions::max_depth`.
+pub fn write(gon: &Gon, style: WriteStyle) -> String {
+    let mut out = String::new();
+    write_to(&mut out, gon, style).expect("Writing to a string can't fail");
+    out
+}
+
+/// Writes the GON to any `fmt::Write` in the provided style.
+/// The braces of a top-level object are omitted, like in hand-written GON files.
+pub fn write_to<W: Write + ?Sized>(out: &mut W, gon: &Gon, style: WriteStyle) -> fmt::Result {
+    let mut writer = Writer { out, style, depth: 0 };
+    match gon {
+        Gon::Object(map) => writer.entries(map),
+        _ => writer.val(gon)
+    }
+}
+
+/// Writes the GON as a nested value, so objects keep their braces.
+pub(crate) fn write_value_to<W: Write + ?Sized>(out: &mut W, gon: &Gon, style: WriteStyle) -> fmt::Result {
+    Writer { out, style, depth: 0 }.val(gon)
+}
+
+/// Checks if a string has to be quoted to be parsed back as the same string.
+fn needs_quotes(s: &str) -> bool {
+    s.is_empty() || s.chars().any(|c| matches!(c,
+        ' ' | '\t' | '\n' | '\r' | '{' | '}' | '[' | ']' | ':' | ',' | '#' | '"' | '\\'
+    ) || c.is_control())
+}
+
+struct Writer<'w, W: Write + ?Sized> {
+    out: &'w mut W,
+    style: WriteStyle,
+    depth: usize
+}
+impl<'w, W: Write + ?Sized> Writer<'w, W> {
+    fn val(&mut self, gon: &Gon) -> fmt::Result {
+        match gon {
+            Gon::Value(val) => self.string(val, val.quoted),
+            Gon::Object(map) if map.is_empty() => self.out.write_str("{}"),
+            Gon::Object(map) => {
+                self.out.write_char('{')?;
+                self.depth += 1;
+                self.line_break()?;
+                self.entries(map)?;
+                self.depth -= 1;
+                self.line_break()?;
+                self.out.write_char('}')
+            }
+            Gon::Array(arr) => {
+                let multiline = matches!(self.style, WriteStyle::Pretty { .. })
+                    && arr.iter().any(|val| !matches!(val, Gon::Value(_)));
+                self.out.write_char('[')?;
+                if multiline {
+                    self.depth += 1;
+                    for val in arr {
+                        self.newline()?;
+                        self.val(val)?;
+                    }
+                    self.depth -= 1;
+                    self.newline()?;
+                } else {
+                    for (i, val) in arr.iter().enumerate() {
+                        if i != 0 {
+                            self.out.write_char(' ')?;
+                        }
+                        self.val(val)?;
+                    }
+                }
+                self.out.write_char(']')
+            }
+        }
+    }
+
+    fn entries(&mut self, map: &IndexMap<String, Gon>) -> fmt::Result {
+        for (i, (key, val)) in map.iter().enumerate() {
+            if i != 0 {
+                self.separator()?;
+            }
+            self.string(key, false)?;
+            self.out.write_char(' ')?;
+            self.val(val)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a string, quoting it if it was quoted in the source or if it has to be quoted.
+    fn string(&mut self, s: &str, quoted: bool) -> fmt::Result {
+        if !quoted && !needs_quotes(s) {
+            return self.out.write_str(s);
+        }
+        self.out.write_char('"')?;
+        for c in s.chars() {
+            match c {
+                '"' => self.out.write_str("\\\"")?,
+                '\\' => self.out.write_str("\\\\")?,
+                '\x08' => self.out.write_str("\\b")?,
+                '\x0C' => self.out.write_str("\\f")?,
+                '\n' => self.out.write_str("\\n")?,
+                '\r' => self.out.write_str("\\r")?,
+                '\t' => self.out.write_str("\\t")?,
+                c if c.is_control() => write!(self.out, "\\u{:04x}", c as u32)?,
+                c => self.out.write_char(c)?
+            }
+        }
+        self.out.write_char('"')
+    }
+
+    /// Separates two object entries.
+    fn separator(&mut self) -> fmt::Result {
+        match self.style {
+            WriteStyle::Compact => self.out.write_char(' '),
+            WriteStyle::Pretty { .. } => self.newline()
+        }
+    }
+
+    /// Breaks the line after an opening or before a closing brace in pretty style.
+    fn line_break(&mut self) -> fmt::Result {
+        match self.style {
+            WriteStyle::Compact => Ok(()),
+            WriteStyle::Pretty { .. } => self.newline()
+        }
+    }
+
+    fn newline(&mut self) -> fmt::Result {
+        self.out.write_char('\n')?;
+        if let WriteStyle::Pretty { indent } = self.style {
+            for _ in 0..indent * self.depth {
+                self.out.write_char(' ')?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use crate::Gon;
+    use super::{write, WriteStyle};
+
+    fn round_trip(gon: &Gon) {
+        for style in [WriteStyle::Compact, WriteStyle::PRETTY, WriteStyle::Pretty { indent: 0 }] {
+            let text = write(gon, style);
+            assert_eq!(&Gon::parse(&text).unwrap(), gon, "written as:\n{text}");
+        }
+    }
+
+    #[test]
+    fn write_simple() {
+        let gon = Gon::Object(IndexMap::from([
+            ("weekdays".to_owned(), Gon::Array(vec![Gon::Value("Monday".into()), Gon::Value("Tuesday".into())]))
+        ]));
+        assert_eq!(write(&gon, WriteStyle::Compact), "weekdays [Monday Tuesday]");
+        assert_eq!(write(&gon, WriteStyle::PRETTY), "weekdays [Monday Tuesday]");
+
+        let gon = Gon::Object(IndexMap::from([
+            ("factory".to_owned(), Gon::Object(IndexMap::from([
+                ("location".to_owned(), Gon::Value("New York City".into()))
+            ])))
+        ]));
+        assert_eq!(write(&gon, WriteStyle::Compact), r#"factory {location "New York City"}"#);
+        assert_eq!(write(&gon, WriteStyle::PRETTY), "factory {\n    location \"New York City\"\n}");
+    }
+
+    #[test]
+    fn write_round_trip() {
+        round_trip(&Gon::parse(r#"
+            big_factory {
+                location "New York City"
+                whirly_widgets 8346
+                products [{ name A } { name "B C" tags [] } {}]
+            }
+            empty {}
+            "weird key: #1" "quotes \" and \\ backslashes"
+            whitespace "\t\n\r"
+            control "\u0000\u001b\u007f"
+            "" ""
+            nested [[1 2] [3 [4]] []]
+        "#).unwrap());
+
+        let quoted = Gon::parse(r#"id "123" name "abc" enabled "true" count 5"#).unwrap();
+        assert_eq!(write(&quoted, WriteStyle::Compact), r#"id "123" name "abc" enabled "true" count 5"#);
+        round_trip(&quoted);
+
+        round_trip(&Gon::Value(String::new().into()));
+        round_trip(&Gon::Value("Hello World".into()));
+        round_trip(&Gon::Value("#not_a_comment".into()));
+        round_trip(&Gon::Value("{".into()));
+        round_trip(&Gon::Array(vec![]));
+        round_trip(&Gon::Array(vec![Gon::Object(IndexMap::new()), Gon::Value(",".into())]));
+        round_trip(&Gon::Object(IndexMap::new()));
+    }
+}
@@ -1,4 +1,4 @@
-use gon_rs::{FromGon, from::FromGon};
+use gon_rs::{FromGon, ToGon, from::FromGon, to::ToGon};
 
 
 
@@ -22,4 +22,454 @@ fn derive_test() {
 
     let gon = gon_rs::Gon::parse(gon_str).unwrap();
     assert_eq!(Example::from_gon(&gon).unwrap(), Example { a: 5, b: AnEnum::ValueB })
-}
\ No newline at end of file
+}
+
+#[test]
+fn derive_to_gon() {
+    #[derive(FromGon, ToGon, PartialEq, Debug)]
+    enum Kind {
+        Small,
+        Big
+    }
+    #[derive(FromGon, ToGon, PartialEq, Debug)]
+    struct Pair(i32, String);
+    #[derive(FromGon, ToGon, PartialEq, Debug)]
+    struct Marker;
+    #[derive(FromGon, ToGon, PartialEq, Debug)]
+    struct Factory<T> {
+        location: String,
+        kind: Kind,
+        widgets: Vec<T>,
+        pair: Pair,
+        marker: Marker
+    }
+
+    let factory = Factory {
+        location: "New York City".to_owned(),
+        kind: Kind::Big,
+        widgets: vec![8346u32, 854687],
+        pair: Pair(-1, "one".to_owned()),
+        marker: Marker
+    };
+    let gon = factory.to_gon();
+    assert_eq!(gon["kind"].str(), "Big");
+    assert_eq!(gon["pair"][1].str(), "one");
+    assert_eq!(Factory::from_gon(&gon).unwrap(), factory);
+
+    let parsed = gon_rs::Gon::parse(&format!("{gon:#}")).unwrap();
+    assert_eq!(Factory::<u32>::from_gon(&parsed).unwrap(), factory);
+}
+
+#[test]
+fn derive_from_borrowed_gon() {
+    use std::borrow::Cow;
+    use gon_rs::{FromBorrowedGon, borrowed::{BorrowedGon, FromBorrowedGon}};
+
+    #[derive(FromBorrowedGon, PartialEq, Debug)]
+    enum Kind {
+        Small,
+        Big
+    }
+    #[derive(FromBorrowedGon, PartialEq, Debug)]
+    struct Pair<'a>(i32, &'a str);
+    #[derive(FromBorrowedGon, PartialEq, Debug)]
+    struct Factory<'a, T> {
+        location: &'a str,
+        motto: Cow<'a, str>,
+        kind: Kind,
+        widgets: Vec<T>,
+        pair: Pair<'a>
+    }
+
+    let src = r#"
+        location "New York City"
+        motto "\"Widgets!\""
+        kind Big
+        widgets [8346 854687]
+        pair [-1 one]
+    "#;
+    let gon = BorrowedGon::parse(src).unwrap();
+    let factory = Factory::from_borrowed_gon(&gon).unwrap();
+    assert_eq!(factory, Factory {
+        location: "New York City",
+        motto: Cow::Owned("\"Widgets!\"".to_owned()),
+        kind: Kind::Big,
+        widgets: vec![8346u32, 854687],
+        pair: Pair(-1, "one")
+    });
+    // unescaped strings point into the source
+    assert!(src.as_bytes().as_ptr_range().contains(&factory.location.as_ptr()));
+}
+
+#[test]
+fn derive_depth_limit() {
+    #[derive(FromGon)]
+    struct Tree {
+        children: Vec<Tree>
+    }
+    let nested = |depth: usize| format!("{}{}", "{ children [".repeat(depth), "] }".repeat(depth));
+    let options = gon_rs::ParseOptions { max_depth: None, ..gon_rs::ParseOptions::new() };
+
+    let gon = gon_rs::Gon::parse_with(&nested(100), &options).unwrap();
+    assert_eq!(Tree::from_gon(&gon).unwrap().children.len(), 1);
+    let gon = gon_rs::Gon::parse_with(&nested(130), &options).unwrap();
+    assert!(matches!(Tree::from_gon(&gon), Err(gon_rs::from::FromGonError::DepthLimitExceeded(128))));
+
+    // the limit can be raised along with the parser's
+    let options = gon_rs::ParseOptions::new().max_depth(300);
+    let gon = gon_rs::Gon::parse_with(&nested(140), &options).unwrap();
+    let with_limit = |max_depth| Tree::from_gon_with_context(&gon, gon_rs::from::Context::new().max_depth(max_depth));
+    assert!(with_limit(options.max_depth).is_ok());
+    assert!(matches!(with_limit(Some(50)), Err(gon_rs::from::FromGonError::DepthLimitExceeded(50))));
+    assert!(with_limit(None).is_ok());
+    assert!(Tree::from_gon(&gon).is_err());
+}
+
+#[test]
+fn derive_rename() {
+    use gon_rs::{FromBorrowedGon, borrowed::{BorrowedGon, FromBorrowedGon}};
+
+    #[derive(FromGon, FromBorrowedGon, ToGon, PartialEq, Debug)]
+    #[gon(rename_all = "kebab-case")]
+    enum Kind {
+        SmallFactory,
+        #[gon(rename = "huge", alias = "big", alias = "BIG")]
+        BigFactory
+    }
+    #[derive(FromGon, FromBorrowedGon, ToGon, PartialEq, Debug)]
+    #[gon(rename_all = "PascalCase")]
+    struct Factory {
+        whirly_widgets: u32,
+        #[gon(alias = "type")]
+        kind: Kind,
+        #[gon(rename = "where")]
+        location: String,
+        r#loop: u8
+    }
+
+    let src = "WhirlyWidgets 8346 type big where Paris Loop 1";
+    let factory = Factory { whirly_widgets: 8346, kind: Kind::BigFactory, location: "Paris".to_owned(), r#loop: 1 };
+    assert_eq!(Factory::from_gon(&gon_rs::Gon::parse(src).unwrap()).unwrap(), factory);
+    assert_eq!(Factory::from_borrowed_gon(&BorrowedGon::parse(src).unwrap()).unwrap(), factory);
+    assert_eq!(factory.to_gon().to_string(), "WhirlyWidgets 8346 Kind huge where Paris Loop 1");
+    assert_eq!(Kind::from_gon(&gon_rs::Gon::Value("small-factory".into())).unwrap(), Kind::SmallFactory);
+    assert!(Kind::from_gon(&gon_rs::Gon::Value("SmallFactory".into())).is_err());
+    assert!(matches!(
+        Factory::from_gon(&gon_rs::Gon::parse("whirly_widgets 1").unwrap()),
+        Err(gon_rs::from::FromGonError::Missing(&"WhirlyWidgets"))
+    ));
+
+    #[derive(ToGon)]
+    #[gon(rename_all = "camelCase")]
+    struct Cases {
+        one_two_three: u8,
+        #[gon(rename = "SAME")]
+        same: u8
+    }
+    assert_eq!(Cases { one_two_three: 1, same: 2 }.to_gon().to_string(), "oneTwoThree 1 SAME 2");
+}
+
+#[test]
+fn derive_defaults() {
+    use gon_rs::{FromBorrowedGon, borrowed::{BorrowedGon, FromBorrowedGon}};
+
+    fn default_retries() -> u8 {
+        3
+    }
+    #[derive(FromGon, FromBorrowedGon, ToGon, PartialEq, Debug)]
+    struct Config {
+        name: String,
+        port: Option<u16>,
+        fallback: Option<Vec<u16>>,
+        #[gon(default)]
+        tags: Vec<String>,
+        #[gon(default = "default_retries")]
+        retries: u8
+    }
+    let parse = |s: &str| Config::from_gon(&gon_rs::Gon::parse(s).unwrap());
+
+    let config = parse("name server port 80").unwrap();
+    assert_eq!(config, Config { name: "server".to_owned(), port: Some(80), fallback: None, tags: vec![], retries: 3 });
+    assert_eq!(Config::from_borrowed_gon(&BorrowedGon::parse("name server port 80").unwrap()).unwrap(), config);
+    let config = parse("name server port null fallback [1 2] tags [a] retries 0").unwrap();
+    assert_eq!(config, Config { name: "server".to_owned(), port: None, fallback: Some(vec![1, 2]), tags: vec!["a".to_owned()], retries: 0 });
+    assert_eq!(parse(&config.to_gon().to_string()).unwrap(), config);
+    assert!(matches!(parse("port 80"), Err(gon_rs::from::FromGonError::Missing(&"name"))));
+    assert!(parse("name server port \"null\"").is_err());
+
+    #[derive(FromGon, PartialEq, Debug)]
+    #[gon(default)]
+    struct Window {
+        width: u32,
+        height: u32,
+        title: Option<String>
+    }
+    impl Default for Window {
+        fn default() -> Self {
+            Self { width: 800, height: 600, title: Some("Untitled".to_owned()) }
+        }
+    }
+    let window = Window::from_gon(&gon_rs::Gon::parse("height 480").unwrap()).unwrap();
+    assert_eq!(window, Window { width: 800, height: 480, title: Some("Untitled".to_owned()) });
+}
+
+#[test]
+fn derive_enums_with_data() {
+    use gon_rs::{Gon, FromBorrowedGon, borrowed::{BorrowedGon, FromBorrowedGon}, from::FromGonError};
+
+    #[derive(FromGon, FromBorrowedGon, ToGon, PartialEq, Debug)]
+    struct Stats {
+        heal: u32
+    }
+    macro_rules! item {
+        ($name: ident $(, $attr: meta)*) => {
+            #[derive(FromGon, FromBorrowedGon, ToGon, PartialEq, Debug)]
+            #[gon($($attr),*)]
+            enum $name {
+                Weapon { damage: u32, #[gon(default)] range: u32 },
+                Potion(Stats),
+                Pair(u32, String),
+                Junk
+            }
+        };
+    }
+    fn check<T: FromGon + for<'a> FromBorrowedGon<'a> + ToGon + PartialEq + std::fmt::Debug>(src: &str, expected: T) {
+        let gon = Gon::parse(src).unwrap();
+        assert_eq!(T::from_gon(&gon).unwrap(), expected, "{src}");
+        assert_eq!(T::from_borrowed_gon(&BorrowedGon::parse(src).unwrap()).unwrap(), expected, "{src}");
+        assert_eq!(expected.to_gon(), gon, "{src}");
+    }
+
+    item!(External);
+    check("Weapon { damage 5 range 0 }", External::Weapon { damage: 5, range: 0 });
+    check("Potion { heal 10 }", External::Potion(Stats { heal: 10 }));
+    check("Pair [1 one]", External::Pair(1, "one".to_owned()));
+    check("Junk", External::Junk);
+    assert_eq!(External::from_gon(&Gon::parse("Weapon { damage 5 }").unwrap()).unwrap(), External::Weapon { damage: 5, range: 0 });
+    assert!(matches!(External::from_gon(&Gon::parse("Shield {}").unwrap()), Err(FromGonError::UnexpectedVariant(v)) if v == "Shield"));
+    assert!(matches!(External::from_gon(&Gon::parse("a 1 b 2").unwrap()), Err(FromGonError::InvalidLength { expected: 1, found: 2 })));
+
+    #[derive(FromGon, FromBorrowedGon, ToGon, PartialEq, Debug)]
+    #[gon(tag = "type", rename_all = "snake_case")]
+    enum Internal {
+        #[gon(rename_all = "UPPERCASE")]
+        Weapon { damage: u32 },
+        Potion(Stats),
+        JunkItem
+    }
+    check("type weapon DAMAGE 5", Internal::Weapon { damage: 5 });
+    check("type potion heal 10", Internal::Potion(Stats { heal: 10 }));
+    check("type junk_item", Internal::JunkItem);
+    assert!(matches!(Internal::from_gon(&Gon::parse("heal 10").unwrap()), Err(FromGonError::Missing(&"type"))));
+
+    item!(Adjacent, tag = "type", content = "data");
+    check("type Weapon data { damage 5 range 2 }", Adjacent::Weapon { damage: 5, range: 2 });
+    check("type Potion data { heal 10 }", Adjacent::Potion(Stats { heal: 10 }));
+    check("type Pair data [1 one]", Adjacent::Pair(1, "one".to_owned()));
+    check("type Junk", Adjacent::Junk);
+    assert!(matches!(Adjacent::from_gon(&Gon::parse("type Potion").unwrap()), Err(FromGonError::Missing(&"data"))));
+
+    item!(Untagged, untagged);
+    check("damage 5 range 1", Untagged::Weapon { damage: 5, range: 1 });
+    check("heal 10", Untagged::Potion(Stats { heal: 10 }));
+    check("[1 one]", Untagged::Pair(1, "one".to_owned()));
+    check("Junk", Untagged::Junk);
+    assert!(matches!(Untagged::from_gon(&Gon::parse("[1 2 3]").unwrap()), Err(FromGonError::NoMatchingVariant)));
+}
+
+#[test]
+fn derive_unknown_fields() {
+    use gon_rs::{Gon, FromBorrowedGon, borrowed::{BorrowedGon, FromBorrowedGon}, from::{collect_unknown_fields, FromGonError, UnknownField}};
+
+    #[derive(FromGon, FromBorrowedGon, PartialEq, Debug)]
+    #[gon(deny_unknown_fields)]
+    struct Strict {
+        #[gon(alias = "place")]
+        location: String,
+        whirly_widgets: u32
+    }
+    let parse = |s: &str| Strict::from_gon(&Gon::parse(s).unwrap());
+    let strict = Strict { location: "here".to_owned(), whirly_widgets: 3 };
+    assert_eq!(parse("location here whirly_widgets 3").unwrap(), strict);
+    assert_eq!(parse("place here whirly_widgets 3").unwrap(), strict);
+    let err = parse("location here whirly_widgts 3").unwrap_err();
+    let FromGonError::UnknownField(unknown) = err else { panic!("{err}") };
+    assert_eq!(unknown, UnknownField { key: "whirly_widgts".to_owned(), suggestion: Some("whirly_widgets") });
+    assert_eq!(unknown.to_string(), "unknown field 'whirly_widgts', did you mean 'whirly_widgets'?");
+    let err = Strict::from_borrowed_gon(&BorrowedGon::parse("location here whirly_widgets 3 size 1").unwrap()).unwrap_err();
+    assert!(matches!(err, FromGonError::UnknownField(UnknownField { key, suggestion: None }) if key == "size"));
+
+    #[derive(FromGon, PartialEq, Debug)]
+    struct Inner {
+        id: u32
+    }
+    #[derive(FromGon, PartialEq, Debug)]
+    #[gon(tag = "type")]
+    enum Lenient {
+        Single { inner: Inner },
+        Wrapped(Inner)
+    }
+    let gon = Gon::parse("type Single inner { id 1 ib 2 } extra x").unwrap();
+    let (res, unknown) = collect_unknown_fields::<Lenient>(&gon);
+    assert_eq!(res.unwrap(), Lenient::Single { inner: Inner { id: 1 } });
+    let keys: Vec<_> = unknown.iter().map(|unknown| (unknown.key.as_str(), unknown.suggestion)).collect();
+    assert_eq!(keys, [("extra", None), ("ib", Some("id"))]);
+    let gon = Gon::parse("type Wrapped id 1").unwrap();
+    let (res, unknown) = collect_unknown_fields::<Lenient>(&gon);
+    assert_eq!(res.unwrap(), Lenient::Wrapped(Inner { id: 1 }));
+    assert!(unknown.is_empty());
+    // unknown keys are ignored when they aren't collected
+    assert!(Lenient::from_gon(&Gon::parse("type Wrapped id 1 extra x").unwrap()).is_ok());
+
+    // the tag is known to the content of a newtype variant, even if it denies unknown fields
+    #[derive(FromGon, ToGon, PartialEq, Debug)]
+    #[gon(deny_unknown_fields)]
+    struct Stats {
+        power: u32
+    }
+    #[derive(FromGon, ToGon, PartialEq, Debug)]
+    #[gon(tag = "type")]
+    enum Loot {
+        Potion(Stats)
+    }
+    let potion = Loot::Potion(Stats { power: 2 });
+    assert_eq!(Loot::from_gon(&potion.to_gon()).unwrap(), potion);
+    let err = Loot::from_gon(&Gon::parse("type Potion power 2 size 1").unwrap()).unwrap_err();
+    assert!(matches!(err, FromGonError::UnknownField(UnknownField { key, suggestion: None }) if key == "size"));
+
+    #[derive(FromGon, PartialEq, Debug)]
+    #[gon(untagged, deny_unknown_fields)]
+    enum StrictShape {
+        Circle { radius: u32 },
+        Rect { width: u32, height: u32 }
+    }
+    assert!(matches!(StrictShape::from_gon(&Gon::parse("width 2 height 3 radius 1").unwrap()), Err(FromGonError::NoMatchingVariant)));
+
+    #[derive(FromGon, PartialEq, Debug)]
+    #[gon(untagged)]
+    enum Shape {
+        Circle { radius: u32 },
+        Rect { width: u32, height: u32 }
+    }
+    // only the unknown fields of the matching variant are collected
+    let gon = Gon::parse("width 2 height 3 depth 1").unwrap();
+    let (res, unknown) = collect_unknown_fields::<Shape>(&gon);
+    assert_eq!(res.unwrap(), Shape::Rect { width: 2, height: 3 });
+    assert_eq!(unknown, [UnknownField { key: "depth".to_owned(), suggestion: None }]);
+
+    // the collector is passed on through collections and to borrowed conversions
+    #[derive(FromGon, FromBorrowedGon, PartialEq, Debug)]
+    struct Inventory {
+        items: Vec<Item>
+    }
+    #[derive(FromGon, FromBorrowedGon, PartialEq, Debug)]
+    struct Item {
+        id: u32
+    }
+    let src = "items [{ id 1 } { id 2 ib 3 }]";
+    let (res, unknown) = collect_unknown_fields::<Inventory>(&Gon::parse(src).unwrap());
+    assert_eq!(res.unwrap(), Inventory { items: vec![Item { id: 1 }, Item { id: 2 }] });
+    assert_eq!(unknown, [UnknownField { key: "ib".to_owned(), suggestion: Some("id") }]);
+    let gon = BorrowedGon::parse(src).unwrap();
+    let (res, unknown) = gon_rs::borrowed::collect_unknown_fields::<Inventory>(&gon);
+    assert!(res.is_ok());
+    assert_eq!(unknown, [UnknownField { key: "ib".to_owned(), suggestion: Some("id") }]);
+}
+
+#[test]
+fn derive_flatten() {
+    use std::collections::HashMap;
+    use gon_rs::{Gon, FromBorrowedGon, borrowed::{BorrowedGon, FromBorrowedGon}, from::{collect_unknown_fields, FromGonError}};
+
+    #[derive(FromGon, FromBorrowedGon, ToGon, PartialEq, Debug)]
+    struct Base {
+        id: u32,
+        #[gon(alias = "title")]
+        name: String
+    }
+    #[derive(FromGon, FromBorrowedGon, ToGon, PartialEq, Debug)]
+    struct Position {
+        x: i32,
+        y: i32
+    }
+    #[derive(FromGon, FromBorrowedGon, ToGon, PartialEq, Debug)]
+    #[gon(deny_unknown_fields)]
+    struct Entity {
+        #[gon(flatten)]
+        base: Base,
+        health: u32,
+        #[gon(flatten)]
+        position: Option<Position>
+    }
+    let src = "id 1 name tree health 10 x 2 y 3";
+    let entity = Entity {
+        base: Base { id: 1, name: "tree".to_owned() },
+        health: 10,
+        position: Some(Position { x: 2, y: 3 })
+    };
+    let gon = Gon::parse(src).unwrap();
+    assert_eq!(Entity::from_gon(&gon).unwrap(), entity);
+    assert_eq!(Entity::from_borrowed_gon(&BorrowedGon::parse(src).unwrap()).unwrap(), entity);
+    assert_eq!(entity.to_gon(), gon);
+    assert!(Entity::from_gon(&Gon::parse("id 1 title tree health 10 x 2 y 3").unwrap()).is_ok());
+    let err = Entity::from_gon(&Gon::parse("id 1 name tree health 10 x 2 y 3 z 4").unwrap()).unwrap_err();
+    assert!(matches!(err, FromGonError::UnknownField(unknown) if unknown.key == "z"));
+    assert!(matches!(Entity::from_gon(&Gon::parse("id 1 health 10").unwrap()), Err(FromGonError::Missing(&"name"))));
+    // an optional flattened struct is None without any of its keys, but still needs all of them otherwise
+    let gon = Gon::parse("id 1 name tree health 10").unwrap();
+    assert_eq!(Entity::from_gon(&gon).unwrap().position, None);
+    assert_eq!(Entity::from_borrowed_gon(&BorrowedGon::parse("id 1 name tree health 10").unwrap()).unwrap().position, None);
+    assert!(matches!(Entity::from_gon(&Gon::parse("id 1 name tree health 10 x 2").unwrap()), Err(FromGonError::Missing(&"y"))));
+
+    #[derive(FromBorrowedGon, PartialEq, Debug)]
+    struct Moddable<'a> {
+        #[gon(flatten)]
+        base: Base,
+        #[gon(flatten)]
+        extra: HashMap<&'a str, BorrowedGon<'a>>
+    }
+    let src = "id 1 name tree leaves { color green }";
+    let gon = BorrowedGon::parse(src).unwrap();
+    let moddable = Moddable::from_borrowed_gon(&gon).unwrap();
+    assert_eq!(moddable.base, Base { id: 1, name: "tree".to_owned() });
+    assert_eq!(moddable.extra.keys().collect::<Vec<_>>(), [&"leaves"]);
+    assert_eq!(moddable.extra["leaves"]["color"].str(), "green");
+
+    #[derive(FromGon, ToGon, PartialEq, Debug)]
+    struct OwnedModdable {
+        #[gon(flatten)]
+        base: Base,
+        #[gon(flatten)]
+        extra: HashMap<String, Gon>
+    }
+    let gon = Gon::parse(src).unwrap();
+    let (moddable, unknown) = collect_unknown_fields::<OwnedModdable>(&gon);
+    let moddable = moddable.unwrap();
+    assert!(unknown.is_empty());
+    assert_eq!(moddable.extra, HashMap::from([("leaves".to_owned(), Gon::parse("{ color green }").unwrap())]));
+    assert_eq!(moddable.to_gon(), gon);
+
+    #[derive(FromGon, ToGon, PartialEq, Debug)]
+    struct Log {
+        #[gon(flatten)]
+        base: Base,
+        entries: Vec<u32>
+    }
+    let log = Log { base: Base { id: 1, name: "tree".to_owned() }, entries: vec![2, 3] };
+    let gon = Gon::parse("id 1 name tree entries [2 3]").unwrap();
+    assert_eq!(log.to_gon(), gon);
+    assert_eq!(Log::from_gon(&gon).unwrap(), log);
+
+    // the keys of flattened structs aren't unknown to them
+    let gon = Gon::parse("id 1 name tree health 10 x 2 y 3 leaves 4").unwrap();
+    #[derive(FromGon, PartialEq, Debug)]
+    struct Lenient {
+        #[gon(flatten)]
+        base: Base,
+        #[gon(flatten)]
+        position: Position
+    }
+    let (res, unknown) = collect_unknown_fields::<Lenient>(&gon);
+    assert!(res.is_ok());
+    assert_eq!(unknown.iter().map(|unknown| unknown.key.as_str()).collect::<Vec<_>>(), ["health", "leaves"]);
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "serde")]
+
+use std::collections::HashMap;
+
+use gon_rs::Gon;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum Item {
+    Nothing,
+    Coins(u32),
+    Weapon { damage: i32, name: String },
+    Pair(u8, char)
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Factory {
+    location: String,
+    whirly_widgets: u64,
+    efficiency: f32,
+    open: bool,
+    manager: Option<String>,
+    parent: Option<String>,
+    items: Vec<Item>,
+    stock: HashMap<u32, Vec<i8>>,
+    extra: Gon
+}
+
+#[test]
+fn deserialize() {
+    let factory: Factory = gon_rs::from_str(r#"
+        location "New York City"
+        whirly_widgets 8346
+        efficiency 0.75
+        open true
+        manager "Tyler"
+        parent null
+        items [
+            Nothing
+            { Coins 12 }
+            { Weapon { damage 5 name Sword } }
+            { Pair [1 x] }
+        ]
+        stock { 1 [1 2] 2 [] }
+        extra { anything [goes here] }
+    "#).unwrap();
+
+    assert_eq!(factory, Factory {
+        location: "New York City".to_owned(),
+        whirly_widgets: 8346,
+        efficiency: 0.75,
+        open: true,
+        manager: Some("Tyler".to_owned()),
+        parent: None,
+        items: vec![
+            Item::Nothing,
+            Item::Coins(12),
+            Item::Weapon { damage: 5, name: "Sword".to_owned() },
+            Item::Pair(1, 'x')
+        ],
+        stock: HashMap::from([(1, vec![1, 2]), (2, vec![])]),
+        extra: Gon::parse("anything [goes here]").unwrap()
+    });
+
+    // a quoted null is a string
+    assert_eq!(gon_rs::from_str::<Option<String>>(r#""null""#).unwrap(), Some("null".to_owned()));
+    // strings that look like other types keep their quotes
+    let manager = Factory { manager: Some("true".to_owned()), ..factory };
+    assert!(gon_rs::to_gon(&manager).unwrap()["manager"].is_quoted());
+    assert!(gon_rs::to_gon(&manager).unwrap()["open"].is_bool());
+    let factory = Factory { manager: Some("Tyler".to_owned()), ..manager };
+
+    let text = gon_rs::to_string_pretty(&factory).unwrap();
+    assert_eq!(gon_rs::from_str::<Factory>(&text).unwrap(), factory);
+    assert_eq!(gon_rs::to_gon(&factory).unwrap(), Gon::parse(&gon_rs::to_string(&factory).unwrap()).unwrap());
+}
+
+#[test]
+fn borrowed_and_errors() {
+    #[derive(Deserialize)]
+    struct Borrowed<'a> {
+        name: &'a str
+    }
+    let gon = Gon::parse("name borrowed").unwrap();
+    let borrowed: Borrowed = gon_rs::from_gon(&gon).unwrap();
+    assert_eq!(borrowed.name, "borrowed");
+
+    assert!(gon_rs::from_str::<Factory>("location somewhere").is_err());
+    assert!(gon_rs::from_str::<Vec<u8>>("[1 2 300]").is_err());
+    assert!(gon_rs::to_gon(&HashMap::from([((1, 2), 3)])).is_err());
+}
+
+
+#[test]
+fn null_and_floats() {
+    use serde::de::IntoDeserializer;
+
+    let gon: Gon = gon_rs::from_str("a null").unwrap();
+    assert_eq!(gon, Gon::parse("a null").unwrap());
+    // a null from another format is a value as well, not an empty object
+    let unit: Result<Gon, serde::de::value::Error> = Gon::deserialize(().into_deserializer());
+    assert_eq!(unit.unwrap().str(), "null");
+
+    // floats keep a fraction so they aren't read back as integers
+    assert_eq!(gon_rs::to_gon(&1.0f64).unwrap().str(), "1.0");
+    assert!(matches!(&gon_rs::to_gon(&1.0f64).unwrap(), Gon::Value(val) if val.is_float()));
+    assert_eq!(gon_rs::to_gon(&-2.0f32).unwrap().str(), "-2.0");
+    assert_eq!(gon_rs::to_gon(&0.5f64).unwrap().str(), "0.5");
+    let float: Result<Gon, serde::de::value::Error> = Gon::deserialize(3.0f64.into_deserializer());
+    assert_eq!(float.unwrap().str(), "3.0");
+}
+
+#[test]
+fn typed_values() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Base {
+        id: u32,
+        visible: bool
+    }
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Entity {
+        name: String,
+        #[serde(flatten)]
+        base: Base,
+        #[serde(flatten)]
+        rest: HashMap<String, Option<f64>>
+    }
+    let entity: Entity = gon_rs::from_str("name tree id 5 visible true height 2.5 width null").unwrap();
+    assert_eq!(entity.base, Base { id: 5, visible: true });
+    assert_eq!(entity.rest, HashMap::from([("height".to_owned(), Some(2.5)), ("width".to_owned(), None)]));
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(untagged)]
+    enum Setting {
+        Flag(bool),
+        Count(i64),
+        Ratio(f64),
+        Text(String)
+    }
+    let settings: Vec<Setting> = gon_rs::from_str(r#"[true -3 0.5 "7" seven]"#).unwrap();
+    assert_eq!(settings, [
+        Setting::Flag(true), Setting::Count(-3), Setting::Ratio(0.5), Setting::Text("7".to_owned()),
+        Setting::Text("seven".to_owned())
+    ]);
+}